@@ -0,0 +1,67 @@
+use common_macros::b_tree_map;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use num::{BigRational, One};
+
+use galc::algebra::{
+    basis::Basis,
+    metric::{Metric, Square},
+    monom::{Monomial, Symbol},
+    polynom::Polynomial,
+    Product,
+};
+
+/// General multivector with a distinct symbolic coefficient for each basis of the algebra.
+fn multivector(name: &str, metric: &Metric) -> Polynomial {
+    let dimension = metric.dimension();
+    (0..1usize << dimension)
+        .map(|bits| Monomial {
+            scalar: BigRational::one(),
+            symbols: b_tree_map![Symbol::Name(format!("{name}{bits}")) => 1],
+            basis: Basis((0..dimension).map(|i| bits & (1 << i) != 0).collect()),
+        })
+        .fold(Polynomial::default(), |sum, monomial| sum + monomial)
+}
+
+fn bench_algebra(c: &mut Criterion, name: &str, metric: Metric) {
+    let a = multivector("a", &metric);
+    let b = multivector("b", &metric);
+
+    for (product_name, product) in [
+        ("geometric", Product::Geometric),
+        ("exterior", Product::Exterior),
+        ("inner", Product::Inner),
+    ] {
+        c.bench_function(&format!("{name} {product_name} product"), |bencher| {
+            bencher.iter_batched(
+                || (a.clone(), b.clone()),
+                |(a, b)| a.product(product, b, &metric).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+}
+
+fn pga3(c: &mut Criterion) {
+    bench_algebra(
+        c,
+        "PGA 3D",
+        Metric(vec![Square::Pos, Square::Pos, Square::Pos, Square::Zero]),
+    );
+}
+
+fn cga5(c: &mut Criterion) {
+    bench_algebra(
+        c,
+        "CGA 5D",
+        Metric(vec![
+            Square::Pos,
+            Square::Pos,
+            Square::Pos,
+            Square::Pos,
+            Square::Neg,
+        ]),
+    );
+}
+
+criterion_group!(benches, pga3, cga5);
+criterion_main!(benches);
@@ -1,8 +1,12 @@
 pub mod basis;
+pub mod latex;
 pub mod metric;
 pub mod monom;
+pub mod notation;
 pub mod polynom;
+pub mod preset;
 pub mod sign;
+pub mod style;
 
 #[derive(Debug, Clone, Copy)]
 pub enum Product {
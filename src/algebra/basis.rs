@@ -3,7 +3,7 @@ use super::{
     sign::Sign,
 };
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Basis(pub Vec<bool>);
 
 impl Basis {
@@ -17,10 +17,36 @@ impl Basis {
         Basis(vec![true; dimension])
     }
 
+    /// All bases of an algebra, starting with the scalar.
+    pub fn all(dimension: usize) -> impl Iterator<Item = Basis> {
+        (0..1usize << dimension)
+            .map(move |bits| Basis((0..dimension).map(|i| bits >> i & 1 == 1).collect()))
+    }
+
+    /// The same basis in an algebra of another dimension, if it has all its vectors.
+    pub fn embed(&self, dimension: usize) -> Option<Basis> {
+        if self.0.iter().skip(dimension).any(|&vector| vector) {
+            return None;
+        }
+        let mut vectors = self.0.clone();
+        vectors.resize(dimension, false);
+        Some(Basis(vectors))
+    }
+
     pub fn dimension(&self) -> usize {
         self.0.len()
     }
 
+    /// Indices of the vectors spanning this basis, in ascending order.
+    pub fn indices(&self) -> Vec<usize> {
+        self.0
+            .iter()
+            .enumerate()
+            .filter(|(_, &vector)| vector)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
     /// Parity of the reversion operator.
     pub fn reverse(&self) -> Sign {
         let r = self.grade();
@@ -65,7 +91,7 @@ impl Basis {
         let mut vectors = vec![false; self.dimension()];
         let mut sign = Sign::Pos;
 
-        for i in 0..self.dimension() {
+        for (i, vector) in vectors.iter_mut().enumerate() {
             if self.0[i] {
                 for j in 0..i {
                     if rhs.0[j] {
@@ -73,7 +99,7 @@ impl Basis {
                     }
                 }
             }
-            vectors[i] = match (self.0[i], rhs.0[i]) {
+            *vector = match (self.0[i], rhs.0[i]) {
                 (true, false) | (false, true) => true,
                 (true, true) => match metric.0[i] {
                     Square::Pos => {
@@ -0,0 +1,181 @@
+use itertools::Itertools;
+use num::{BigRational, One, Signed};
+
+use super::{
+    basis::Basis,
+    monom::Monomial,
+    notation::Notation,
+    sign::Sign,
+    style::{is_atomic, sum, Style},
+};
+
+/// LaTeX for typesetting results, e.g. `\frac{1}{2} a^{2}\, \mathbf{e}_{12}`.
+/// Vectors named by a letter and an index, such as `e1` or `γ0`, are written with the index
+/// as a subscript of a macro for the letter, e.g. `\mathbf{e}_{1}` or `\gamma_{0}`.
+pub struct Latex<'a> {
+    notation: &'a Notation,
+    /// Macro of all vectors, such as `\gamma`, instead of the macros of their letters.
+    vector: Option<&'a str>,
+}
+
+impl<'a> Latex<'a> {
+    pub fn new(notation: &'a Notation, vector: Option<&'a str>) -> Latex<'a> {
+        Latex { notation, vector }
+    }
+
+    /// Product of the vectors in this order, such as `e31`.
+    pub fn vectors(&self, vectors: &[usize]) -> String {
+        let named = self
+            .notation
+            .blade_name(vectors)
+            .unwrap_or_else(|| vectors.iter().map(|&i| self.notation.vector(i)).join(" "));
+        self.name(&named)
+    }
+
+    /// Names of a basis or of a product of vectors.
+    /// Consecutive names with the same letter share their macro, e.g. `γ0 γ1` as `\gamma_{01}`.
+    fn name(&self, name: &str) -> String {
+        if name.is_empty() {
+            return String::new();
+        }
+        let words = name.split(' ').map(|word| {
+            let index = word.trim_start_matches(|c: char| !c.is_ascii_digit());
+            (&word[..word.len() - index.len()], index)
+        });
+        words
+            .group_by(|&(letter, index)| (letter, index.is_empty()))
+            .into_iter()
+            .map(|((letter, unindexed), words)| {
+                if unindexed {
+                    words.map(|(letter, _)| self.letter(letter)).join(" ")
+                } else {
+                    let indices = words.map(|(_, index)| index).join("");
+                    let letter = self
+                        .vector
+                        .map_or_else(|| self.letter(letter), str::to_string);
+                    format!("{letter}_{{{indices}}}")
+                }
+            })
+            .join(" ")
+    }
+
+    fn letter(&self, letter: &str) -> String {
+        match letter {
+            "e" => r"\mathbf{e}".to_string(),
+            _ => symbol(letter),
+        }
+    }
+}
+
+impl Style for Latex<'_> {
+    fn basis(&self, basis: &Basis) -> (Sign, String) {
+        let (sign, name) = self.notation.basis(basis);
+        if name == "i" && self.notation.resolve("i").is_none() {
+            return (sign, "I".to_string());
+        }
+        (sign, self.name(&name))
+    }
+
+    fn scalar(&self, scalar: &BigRational) -> String {
+        let sign = if scalar.is_negative() { "-" } else { "" };
+        if scalar.denom().is_one() {
+            format!("{sign}{}", scalar.numer().abs())
+        } else {
+            format!(
+                r"{sign}\frac{{{}}}{{{}}}",
+                scalar.numer().abs(),
+                scalar.denom()
+            )
+        }
+    }
+
+    fn symbol(&self, name: &str, multiplicity: isize) -> String {
+        let name = symbol(name);
+        if multiplicity == 1 {
+            name
+        } else {
+            format!("{name}^{{{multiplicity}}}")
+        }
+    }
+
+    fn power(&self, base: &[Monomial], exponent: &[Monomial], multiplicity: isize) -> String {
+        let base = if is_atomic(base) {
+            sum(base, self)
+        } else {
+            format!(r"\left({}\right)", sum(base, self))
+        };
+        let power = format!("{base}^{{{}}}", sum(exponent, self));
+        if multiplicity == 1 {
+            power
+        } else {
+            format!("{{{power}}}^{{{multiplicity}}}")
+        }
+    }
+
+    fn separator(&self) -> &'static str {
+        r"\, "
+    }
+
+    fn group(&self, coefficient: &str, basis: &str) -> String {
+        format!(r"\left({coefficient}\right)\, {basis}")
+    }
+}
+
+/// Greek letters are written as macros, and names of several letters upright.
+pub fn symbol(name: &str) -> String {
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), None) => match greek(letter) {
+            Some(macro_name) => format!(r"\{macro_name}"),
+            None => name.to_string(),
+        },
+        _ if name.starts_with(|c: char| c.is_ascii_alphabetic())
+            && name.chars().all(|c| c.is_ascii_alphanumeric()) =>
+        {
+            format!(r"\mathrm{{{name}}}")
+        }
+        _ => name.to_string(),
+    }
+}
+
+/// Name of the macro of a Greek letter.
+pub fn greek(letter: char) -> Option<&'static str> {
+    GREEK
+        .iter()
+        .find(|&&(greek, _)| greek == letter)
+        .map(|&(_, name)| name)
+}
+
+/// Greek letter of a macro such as `\gamma`, given without the backslash.
+pub fn greek_letter(name: &str) -> Option<char> {
+    GREEK
+        .iter()
+        .find(|&&(_, macro_name)| macro_name == name)
+        .map(|&(letter, _)| letter)
+}
+
+const GREEK: [(char, &str); 23] = [
+    ('α', "alpha"),
+    ('β', "beta"),
+    ('γ', "gamma"),
+    ('δ', "delta"),
+    ('ε', "epsilon"),
+    ('ζ', "zeta"),
+    ('η', "eta"),
+    ('θ', "theta"),
+    ('ι', "iota"),
+    ('κ', "kappa"),
+    ('λ', "lambda"),
+    ('μ', "mu"),
+    ('ν', "nu"),
+    ('ξ', "xi"),
+    ('π', "pi"),
+    ('ρ', "rho"),
+    ('σ', "sigma"),
+    ('τ', "tau"),
+    ('υ', "upsilon"),
+    ('φ', "phi"),
+    ('χ', "chi"),
+    ('ψ', "psi"),
+    ('ω', "omega"),
+];
@@ -2,6 +2,19 @@
 pub struct Metric(pub Vec<Square>);
 
 impl Metric {
+    /// Metric with `p` positive, `q` negative and `r` degenerate dimensions, in this order.
+    pub fn signature(p: usize, q: usize, r: usize) -> Metric {
+        let mut squares = vec![Square::Pos; p];
+        squares.extend(std::iter::repeat_n(Square::Neg, q));
+        squares.extend(std::iter::repeat_n(Square::Zero, r));
+        Metric(squares)
+    }
+
+    /// Plane-based geometric algebra of `n` dimensions, whose degenerate vector comes last.
+    pub fn pga(n: usize) -> Metric {
+        Metric::signature(n, 0, 1)
+    }
+
     pub fn dimension(&self) -> usize {
         self.0.len()
     }
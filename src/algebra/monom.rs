@@ -1,24 +1,77 @@
 use itertools::Itertools;
 
-use num::{BigRational, One, Signed, Zero};
+use num::{BigInt, BigRational, One, Signed, Zero};
 
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use super::{
     basis::Basis,
     metric::{Metric, Square},
+    notation::Notation,
+    style::Style,
     Product,
 };
 
-#[derive(Debug, Clone, PartialEq)]
+/// Factor of a monomial which commutes with everything.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Symbol {
+    Name(String),
+    /// Power of a base with a symbolic exponent, such as `(1 + a)^n`.
+    /// Both are scalars, whose monomials are in canonical order and have no vectors,
+    /// so that equal powers are merged regardless of the algebra and the names of its bases.
+    Power(Vec<Monomial>, Vec<Monomial>),
+}
+
+impl Symbol {
+    pub fn power(base: Vec<Monomial>, exponent: Vec<Monomial>) -> Symbol {
+        let scalars = |monomials: Vec<Monomial>| {
+            monomials
+                .into_iter()
+                .map(|monomial| Monomial {
+                    basis: Basis::scalar(0),
+                    ..monomial
+                })
+                .collect()
+        };
+        Symbol::Power(scalars(base), scalars(exponent))
+    }
+
+    /// Names of variables in this symbol, including those in powers.
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            Symbol::Name(name) => vec![name],
+            Symbol::Power(base, exponent) => base
+                .iter()
+                .chain(exponent)
+                .flat_map(|monomial| monomial.symbols.keys())
+                .flat_map(Symbol::names)
+                .collect(),
+        }
+    }
+}
+
+/// Symbols and their multiplicities, ordered by name.
+pub type Symbols = BTreeMap<Symbol, isize>;
+
+/// Multiplicity of a symbol beyond the range of multiplicities, e.g. in `a^65536^65536^65536^65536`.
+/// `isize::MIN` is out of range as well, so that multiplicities can always be negated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiplicityOverflow(pub BigInt);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Monomial {
     pub scalar: BigRational,
-    pub symbols: HashMap<String, isize>,
+    pub symbols: Symbols,
     pub basis: Basis,
 }
 
 impl Monomial {
-    pub fn product(&self, product: Product, rhs: &Monomial, metric: &Metric) -> Monomial {
+    pub fn product(
+        &self,
+        product: Product,
+        rhs: &Monomial,
+        metric: &Metric,
+    ) -> Result<Monomial, MultiplicityOverflow> {
         let f = match product {
             Product::Geometric => Basis::geometric_product,
             Product::Exterior => Basis::exterior_product,
@@ -30,25 +83,33 @@ impl Monomial {
         };
         if let Some((sign, basis)) = f(&self.basis, &rhs.basis, metric) {
             let mut symbols = self.symbols.clone();
-            for (name, &multiplicity_rhs) in &rhs.symbols {
-                if let Some(multiplicity) = symbols.get_mut(name) {
-                    *multiplicity += multiplicity_rhs;
+            for (symbol, &multiplicity_rhs) in &rhs.symbols {
+                if let Some(multiplicity) = symbols.get_mut(symbol) {
+                    *multiplicity = multiplicity
+                        .checked_add(multiplicity_rhs)
+                        .filter(|&sum| sum != isize::MIN)
+                        .ok_or_else(|| {
+                            MultiplicityOverflow(BigInt::from(*multiplicity) + multiplicity_rhs)
+                        })?;
+                    if *multiplicity == 0 {
+                        symbols.remove(symbol);
+                    }
                 } else {
-                    symbols.insert(name.clone(), multiplicity_rhs);
+                    symbols.insert(symbol.clone(), multiplicity_rhs);
                 }
             }
 
-            Monomial {
+            Ok(Monomial {
                 scalar: sign * self.scalar.clone() * rhs.scalar.clone(),
                 symbols,
                 basis,
-            }
+            })
         } else {
-            Monomial {
+            Ok(Monomial {
                 scalar: BigRational::zero(),
                 symbols: Default::default(),
                 basis: Basis::scalar(metric.dimension()),
-            }
+            })
         }
     }
 
@@ -84,6 +145,10 @@ impl Monomial {
         }
     }
 
+    pub fn is_zero(&self) -> bool {
+        self.scalar.is_zero()
+    }
+
     pub fn grade(&self) -> usize {
         self.basis.grade()
     }
@@ -93,9 +158,11 @@ impl Monomial {
     }
 
     pub fn norm_squared(&self, metric: &Metric) -> BigRational {
-        self.product(Product::Scalar, &self.conjugate(), metric)
-            .scalar
-            .abs()
+        let conjugate = self.conjugate();
+        match Basis::scalar_product(&self.basis, &conjugate.basis, metric) {
+            Some(_) => (self.scalar.clone() * conjugate.scalar).abs(),
+            None => BigRational::zero(),
+        }
     }
 
     pub fn norm(&self, metric: &Metric) -> BigRational {
@@ -142,13 +209,30 @@ impl Monomial {
         };
 
         for _ in 0..exponent.abs() {
-            power = power.product(Product::Geometric, &monomial, metric);
+            power = power.product(Product::Geometric, &monomial, metric).ok()?;
         }
 
         Some(power)
     }
 }
 
+/// Canonical order of monomials: by grade, basis, symbol signature and lastly by coefficient.
+impl Ord for Monomial {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.grade()
+            .cmp(&other.grade())
+            .then_with(|| self.basis.indices().cmp(&other.basis.indices()))
+            .then_with(|| self.symbols.cmp(&other.symbols))
+            .then_with(|| self.scalar.cmp(&other.scalar))
+    }
+}
+
+impl PartialOrd for Monomial {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl std::ops::Neg for Monomial {
     type Output = Monomial;
 
@@ -173,41 +257,45 @@ impl std::ops::Mul<Monomial> for BigRational {
     }
 }
 
-impl std::fmt::Display for Monomial {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl Monomial {
+    /// Formats the monomial in the given style, e.g. naming its basis according to a notation.
+    pub fn format(&self, style: &impl Style) -> String {
+        let (sign, basis) = style.basis(&self.basis);
+        let scalar = sign * self.scalar.clone();
+
         let symbols = self
             .symbols
             .iter()
-            .sorted_by(|(a, _), (b, _)| a.cmp(b))
-            .map(|(name, &multiplicity)| {
-                if multiplicity == 1 {
-                    format!("{name}")
-                } else {
-                    format!("{name}^{multiplicity}")
-                }
+            .map(|(symbol, &multiplicity)| match symbol {
+                Symbol::Name(name) => style.symbol(name, multiplicity),
+                Symbol::Power(base, exponent) => style.power(base, exponent, multiplicity),
             })
             .join(" ");
 
-        let just_scalar = symbols.is_empty() && self.basis.grade() == 0;
-        if self.scalar == -BigRational::one() && !just_scalar {
-            write!(f, "-")?;
-        } else if self.scalar != BigRational::one() || just_scalar {
-            write!(f, "{}", self.scalar)?;
-            if !just_scalar {
-                write!(f, " ")?;
+        let mut result = String::new();
+        let just_scalar = symbols.is_empty() && basis.is_empty();
+        if scalar == -BigRational::one() && !just_scalar {
+            result.push('-');
+        } else if scalar != BigRational::one() || just_scalar {
+            result.push_str(&style.scalar(&scalar));
+            if !symbols.is_empty() {
+                result.push(' ');
             }
         }
 
-        write!(f, "{symbols}")?;
+        result.push_str(&symbols);
 
-        if self.anti_grade() == 0 && self.basis.dimension() > 0 || self.grade() > 0 {
-            if !symbols.is_empty() {
-                write!(f, " ")?;
-            }
+        if !basis.is_empty() && !result.is_empty() && result != "-" {
+            result.push_str(style.separator());
         }
 
-        write!(f, "{}", self.basis)?;
+        result.push_str(&basis);
+        result
+    }
+}
 
-        Ok(())
+impl std::fmt::Display for Monomial {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.format(&Notation::default()))
     }
 }
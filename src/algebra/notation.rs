@@ -0,0 +1,172 @@
+use itertools::Itertools;
+
+use super::{basis::Basis, sign::Sign};
+
+/// Names of the bases of an algebra, used both for parsing and printing.
+/// By default, vectors are named by their indices as `e0`, `e1`, ...
+/// and blades by concatenating the indices of their vectors, e.g. `e012`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Notation {
+    /// Names of the vectors by index, or none for the default names.
+    vectors: Vec<String>,
+    /// Names of blades, which are products of the vectors with the given indices in this order.
+    blades: Vec<(Vec<usize>, String)>,
+}
+
+impl Notation {
+    pub fn new(vectors: Vec<String>, blades: Vec<(Vec<usize>, String)>) -> Notation {
+        Notation { vectors, blades }
+    }
+
+    /// Vectors `e1` to `en` followed by the degenerate vector `e0`, matching `Metric::pga`.
+    /// Blades start with `e0`, and are oriented as usual in 2 and 3 dimensions, e.g. `e31` and `e032`.
+    pub fn pga(n: usize) -> Notation {
+        let vectors = (1..=n)
+            .map(|i| format!("e{i}"))
+            .chain(std::iter::once("e0".to_string()))
+            .collect();
+        let mut notation = Notation::new(vectors, Vec::new());
+
+        let degenerate = Basis::all(n + 1)
+            .filter(|basis| basis.0[n] && basis.anti_grade() > 0)
+            .map(|basis| {
+                let indices = basis.indices();
+                format!(
+                    "e0{}",
+                    indices[..indices.len() - 1].iter().map(|i| i + 1).join("")
+                )
+            })
+            .collect_vec();
+        let conventional: &[&str] = match n {
+            2 => &["e20"],
+            3 => &["e31", "e021", "e013", "e032"],
+            _ => &[],
+        };
+        for name in degenerate
+            .iter()
+            .map(String::as_str)
+            .chain(conventional.iter().copied())
+        {
+            let vectors = notation.blade(name).unwrap();
+            notation.orient(vectors, name.to_string());
+        }
+        notation
+    }
+
+    /// Number of named vectors, which is zero for the default notation.
+    pub fn dimension(&self) -> usize {
+        self.vectors.len()
+    }
+
+    pub fn vector(&self, index: usize) -> String {
+        match self.vectors.get(index) {
+            Some(name) => name.clone(),
+            None => format!("e{index}"),
+        }
+    }
+
+    /// Vectors of a basis written as `e` followed by digits, such as `e12`.
+    /// By default the digits are indices, otherwise they refer to the vectors named `e1` and `e2`.
+    pub fn digits(&self, digits: &[usize]) -> Option<Vec<usize>> {
+        if self.vectors.is_empty() {
+            return Some(digits.to_vec());
+        }
+        digits
+            .iter()
+            .map(|digit| {
+                let name = format!("e{digit}");
+                self.vectors.iter().position(|vector| *vector == name)
+            })
+            .collect()
+    }
+
+    /// Distinct vectors of a blade written as `e` followed by digits, such as `e31`.
+    pub fn blade(&self, name: &str) -> Option<Vec<usize>> {
+        let digits = name
+            .strip_prefix('e')?
+            .chars()
+            .map(|c| c.to_digit(10).map(|digit| digit as usize))
+            .collect::<Option<Vec<_>>>()?;
+        let vectors = self.digits(&digits)?;
+        (!vectors.is_empty() && vectors.iter().all_unique()).then_some(vectors)
+    }
+
+    /// Names the blade of the given vectors, which is printed in this orientation from now on.
+    /// Replaces a previous name of the same blade.
+    pub fn orient(&mut self, vectors: Vec<usize>, name: String) {
+        self.blades
+            .retain(|(other, _)| !other.iter().sorted().eq(vectors.iter().sorted()));
+        self.blades.push((vectors, name));
+    }
+
+    /// Vectors whose product a name denotes, such as `k` for the product of `i` and `j`.
+    pub fn resolve(&self, name: &str) -> Option<Vec<usize>> {
+        self.blades
+            .iter()
+            .find(|(_, blade)| blade == name)
+            .map(|(vectors, _)| vectors.clone())
+            .or_else(|| {
+                self.vectors
+                    .iter()
+                    .position(|vector| vector == name)
+                    .map(|index| vec![index])
+            })
+    }
+
+    /// Name of the blade of exactly these vectors in this order, if it has one.
+    pub fn blade_name(&self, vectors: &[usize]) -> Option<String> {
+        self.blades
+            .iter()
+            .find(|(blade, _)| blade == vectors)
+            .map(|(_, name)| name.clone())
+    }
+
+    /// Name of a basis, along with the sign of the named blade relative to the basis.
+    pub fn basis(&self, basis: &Basis) -> (Sign, String) {
+        let indices = basis.indices();
+        if indices.is_empty() {
+            return (Sign::Pos, String::new());
+        }
+
+        let blade = self
+            .blades
+            .iter()
+            .find(|(vectors, _)| vectors.iter().sorted().eq(indices.iter()));
+        if let Some((vectors, name)) = blade {
+            return (permutation_sign(vectors), name.clone());
+        }
+
+        if basis.anti_grade() == 0 && self.resolve("i").is_none() {
+            return (Sign::Pos, "i".to_string());
+        }
+
+        let names = indices.iter().map(|&i| self.vector(i)).collect_vec();
+        // Names such as `e1` and `e2` are concatenated to `e12`, others are juxtaposed.
+        let name = if names.iter().all(|name| is_digit_name(name)) {
+            format!("e{}", names.iter().map(|name| &name[1..]).join(""))
+        } else {
+            names.join(" ")
+        };
+        (Sign::Pos, name)
+    }
+}
+
+/// Whether the name is `e` followed by a single digit.
+fn is_digit_name(name: &str) -> bool {
+    name.strip_prefix('e')
+        .is_some_and(|digit| digit.len() == 1 && digit.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Sign of the product of the vectors in the given order, relative to their ascending order.
+fn permutation_sign(vectors: &[usize]) -> Sign {
+    let inversions = vectors
+        .iter()
+        .tuple_combinations()
+        .filter(|(a, b)| a > b)
+        .count();
+    if inversions % 2 == 0 {
+        Sign::Pos
+    } else {
+        Sign::Neg
+    }
+}
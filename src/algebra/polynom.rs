@@ -1,18 +1,51 @@
+use std::collections::{hash_map::Entry, BTreeSet, HashMap};
+
 use itertools::Itertools;
-use num::BigRational;
+use num::{BigRational, One, Signed, Zero};
+
+use super::{
+    basis::Basis,
+    metric::Metric,
+    monom::{Monomial, MultiplicityOverflow, Symbol, Symbols},
+    notation::Notation,
+    style::Style,
+    Product,
+};
+
+/// Bounds the monomial products of a power, since the terms of multi-term bases
+/// multiply with every squaring.
+const MAX_POWER_PRODUCTS: usize = 1 << 14;
 
-use super::{metric::Metric, monom::Monomial, Product};
+/// Bounds the bits of the coefficients of a power, which grow with the exponent,
+/// e.g. those of `2^65536^65536`.
+const MAX_POWER_BITS: u64 = 1 << 20;
 
+/// Why a power cannot be evaluated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PowerError {
+    /// Negative power of a polynomial without an inverse.
+    NonInvertible,
+    /// Power with too many terms to be evaluated.
+    TooManyTerms,
+    /// Power whose coefficients are too large to be evaluated.
+    TooLarge,
+    /// Power whose symbols have too large multiplicities.
+    Overflow(MultiplicityOverflow),
+}
+
+/// Sum of monomials.
+/// Monomials are keyed by their basis and symbols, so that equal terms are merged on insertion.
+/// Invariant: none of the monomials vanishes, so the zero polynomial has no monomials at all.
 #[derive(Debug, Clone)]
 pub struct Polynomial {
-    monomials: Vec<Monomial>,
+    monomials: HashMap<(Basis, Symbols), BigRational>,
 }
 
 /// The zero polynomial.
 impl Default for Polynomial {
     fn default() -> Self {
         Self {
-            monomials: Vec::default(),
+            monomials: HashMap::default(),
         }
     }
 }
@@ -20,163 +53,347 @@ impl Default for Polynomial {
 impl std::ops::Add for Polynomial {
     type Output = Polynomial;
 
-    fn add(self, mut other: Polynomial) -> Self::Output {
-        let mut monomials = self.monomials;
-        monomials.append(&mut other.monomials);
-        Polynomial { monomials }.merge_monomials()
+    fn add(mut self, other: Polynomial) -> Self::Output {
+        for monomial in other.into_monomials() {
+            self.accumulate(monomial);
+        }
+        self
     }
 }
 
 impl std::ops::Add<Monomial> for Polynomial {
     type Output = Polynomial;
 
-    fn add(self, other: Monomial) -> Self::Output {
-        let mut monomials = self.monomials;
-        monomials.push(other);
-        Polynomial { monomials }.merge_monomials()
+    fn add(mut self, other: Monomial) -> Self::Output {
+        self.accumulate(other);
+        self
     }
 }
 
 impl std::ops::Neg for Polynomial {
     type Output = Polynomial;
 
-    fn neg(self) -> Self::Output {
-        Polynomial {
-            monomials: self
-                .monomials
-                .into_iter()
-                .map(|monomial| -monomial)
-                .collect(),
+    fn neg(mut self) -> Self::Output {
+        for scalar in self.monomials.values_mut() {
+            *scalar = -&*scalar;
         }
+        self
     }
 }
 
 impl Polynomial {
-    pub fn product(self, product: Product, other: Polynomial, metric: &Metric) -> Polynomial {
-        let mut result = Polynomial::default();
-        for lhs in self.monomials {
-            for rhs in &other.monomials {
-                result = result + lhs.product(product, rhs, metric);
+    /// Adds a monomial in place.
+    /// If it cancels out an existing monomial, both are removed.
+    fn accumulate(&mut self, monomial: Monomial) {
+        if monomial.is_zero() {
+            return;
+        }
+        match self.monomials.entry((monomial.basis, monomial.symbols)) {
+            Entry::Occupied(mut entry) => {
+                *entry.get_mut() += monomial.scalar;
+                if entry.get().is_zero() {
+                    entry.remove();
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(monomial.scalar);
             }
         }
+    }
+
+    /// Monomials in arbitrary order.
+    fn into_monomials(self) -> impl Iterator<Item = Monomial> {
+        self.monomials
+            .into_iter()
+            .map(|((basis, symbols), scalar)| {
+                debug_assert!(
+                    !scalar.is_zero(),
+                    "Polynomials must not contain vanishing monomials"
+                );
+                Monomial {
+                    scalar,
+                    symbols,
+                    basis,
+                }
+            })
+    }
+
+    /// Monomials in canonical order.
+    pub fn monomials(&self) -> Vec<Monomial> {
+        self.clone().into_monomials().sorted().collect()
+    }
+
+    /// Maps each monomial, merging the results.
+    fn map(self, f: impl Fn(Monomial) -> Monomial) -> Polynomial {
+        let mut result = Polynomial::default();
+        for monomial in self.into_monomials() {
+            result.accumulate(f(monomial));
+        }
         result
     }
 
-    pub fn power(self, exponent: isize, metric: &Metric) -> Option<Polynomial> {
+    pub fn product(
+        self,
+        product: Product,
+        other: Polynomial,
+        metric: &Metric,
+    ) -> Result<Polynomial, MultiplicityOverflow> {
+        let others: Vec<Monomial> = other.into_monomials().collect();
         let mut result = Polynomial::default();
-        for monomial in self.monomials {
-            result = result + monomial.power(exponent, metric)?;
+        for lhs in self.into_monomials() {
+            for rhs in &others {
+                result.accumulate(lhs.product(product, rhs, metric)?);
+            }
         }
-        Some(result)
+        Ok(result)
+    }
+
+    /// Integral power by repeated squaring.
+    /// Negative powers are powers of the inverse.
+    pub fn power(self, exponent: isize, metric: &Metric) -> Result<Polynomial, PowerError> {
+        let mut base = if exponent < 0 {
+            if self.is_zero() {
+                return Err(PowerError::NonInvertible);
+            }
+            self.inverse(metric).ok_or(PowerError::NonInvertible)?
+        } else {
+            self
+        };
+
+        let mut result = Polynomial::from(Monomial {
+            scalar: BigRational::one(),
+            symbols: Default::default(),
+            basis: Basis::scalar(metric.dimension()),
+        });
+        let mut exponent = exponent.unsigned_abs();
+        let bits = base
+            .monomials
+            .values()
+            .map(|scalar| scalar.numer().bits() + scalar.denom().bits())
+            .max()
+            .unwrap_or(0);
+        if bits.saturating_mul(exponent as u64) > MAX_POWER_BITS {
+            return Err(PowerError::TooLarge);
+        }
+
+        let products = |lhs: &Polynomial, rhs: &Polynomial| {
+            let products = lhs.monomials.len() * rhs.monomials.len();
+            if products > MAX_POWER_PRODUCTS {
+                Err(PowerError::TooManyTerms)
+            } else {
+                Ok(())
+            }
+        };
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                products(&result, &base)?;
+                result = result
+                    .product(Product::Geometric, base.clone(), metric)
+                    .map_err(PowerError::Overflow)?;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                products(&base, &base)?;
+                base = base
+                    .clone()
+                    .product(Product::Geometric, base, metric)
+                    .map_err(PowerError::Overflow)?;
+            }
+        }
+        Ok(result)
     }
 
     pub fn inverse(self, metric: &Metric) -> Option<Polynomial> {
-        let mut inverse_monomials = Vec::new();
-        for monomial in self.monomials {
-            inverse_monomials.push(monomial.inverse(metric)?);
+        let mut result = Polynomial::default();
+        for monomial in self.into_monomials() {
+            result.accumulate(monomial.inverse(metric)?);
         }
-        Some(Polynomial {
-            monomials: inverse_monomials,
-        })
+        Some(result)
     }
 
     pub fn dual(self) -> Polynomial {
-        Polynomial {
-            monomials: self
-                .monomials
-                .into_iter()
-                .map(|monomial| monomial.dual())
-                .collect(),
-        }
+        self.map(|monomial| monomial.dual())
     }
 
     pub fn involute(self) -> Polynomial {
-        Polynomial {
-            monomials: self
-                .monomials
-                .into_iter()
-                .map(|monomial| monomial.involute())
-                .collect(),
-        }
+        self.map(|monomial| monomial.involute())
     }
 
     pub fn conjugate(self) -> Polynomial {
-        Polynomial {
-            monomials: self
-                .monomials
-                .into_iter()
-                .map(|monomial| monomial.conjugate())
-                .collect(),
-        }
+        self.map(|monomial| monomial.conjugate())
     }
 
     pub fn reverse(self) -> Polynomial {
-        Polynomial {
-            monomials: self
-                .monomials
-                .into_iter()
-                .map(|monomial| monomial.reverse())
-                .collect(),
+        self.map(|monomial| monomial.reverse())
+    }
+
+    /// Part of the given grade.
+    pub fn grade_projection(self, grade: usize) -> Polynomial {
+        let mut result = Polynomial::default();
+        for monomial in self.into_monomials() {
+            if monomial.grade() == grade {
+                result.accumulate(monomial);
+            }
         }
+        result
     }
 
     pub fn norm(self, metric: &Metric) -> BigRational {
-        self.monomials
-            .into_iter()
+        self.into_monomials()
             .map(|monomial| monomial.norm(metric))
             .sum()
     }
 
-    /// Merges monomials with same bases and same symbols.
-    fn merge_monomials(self) -> Polynomial {
+    /// Dimension of the algebra, which is unknown for zero.
+    pub fn dimension(&self) -> Option<usize> {
+        self.monomials
+            .keys()
+            .next()
+            .map(|(basis, _)| basis.dimension())
+    }
+
+    /// The same polynomial in an algebra of another dimension, if it has all its bases.
+    pub fn embed(self, dimension: usize) -> Option<Polynomial> {
         let mut result = Polynomial::default();
-        for monomial in self.monomials {
-            let mut found_monomial = false;
-            for result_monomial in &mut result.monomials {
-                if monomial.basis == result_monomial.basis
-                    && monomial.symbols == result_monomial.symbols
-                {
-                    result_monomial.scalar += monomial.scalar.clone();
-                    result_monomial
-                        .symbols
-                        .extend(monomial.symbols.clone().into_iter());
-                    found_monomial = true;
-                    break;
-                }
-            }
-            if !found_monomial {
-                result.monomials.push(monomial);
+        for monomial in self.into_monomials() {
+            result.accumulate(Monomial {
+                basis: monomial.basis.embed(dimension)?,
+                ..monomial
+            });
+        }
+        Some(result)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.monomials.is_empty()
+    }
+
+    /// The numeric value, if this is a plain number without symbols and bases.
+    pub fn scalar(&self) -> Option<BigRational> {
+        match self.monomials.iter().collect_vec()[..] {
+            [] => Some(BigRational::zero()),
+            [((basis, symbols), scalar)] if basis.grade() == 0 && symbols.is_empty() => {
+                Some(scalar.clone())
             }
+            _ => None,
         }
-        result
     }
 
-    pub fn optimize(self) -> Polynomial {
-        self.merge_monomials()
+    /// Names of variables in the symbols of this polynomial.
+    pub fn names(&self) -> BTreeSet<&str> {
+        self.monomials
+            .keys()
+            .flat_map(|(_, symbols)| symbols.keys())
+            .flat_map(Symbol::names)
+            .collect()
+    }
+
+    /// Whether all monomials are of grade zero, possibly with symbols.
+    pub fn is_scalar(&self) -> bool {
+        self.monomials.keys().all(|(basis, _)| basis.grade() == 0)
+    }
+
+    /// Formats the monomials in canonical order in the given style.
+    pub fn format(&self, style: &impl Style) -> String {
+        if self.is_zero() {
+            "0".to_string()
+        } else {
+            self.monomials()
+                .iter()
+                .map(|monomial| monomial.format(style))
+                .join(" + ")
+        }
+    }
+
+    /// Layout which collects all monomials sharing a basis into a single coefficient.
+    pub fn collected<'a, S: Style>(&'a self, style: &'a S) -> Collected<'a, S> {
+        Collected(self, style)
     }
 }
 
+/// Algebraic equality, which is structural equality since monomials are always merged.
+impl PartialEq for Polynomial {
+    fn eq(&self, other: &Self) -> bool {
+        self.monomials == other.monomials
+    }
+}
+
+impl Eq for Polynomial {}
+
 impl std::convert::From<Monomial> for Polynomial {
     fn from(monomial: Monomial) -> Self {
-        Polynomial {
-            monomials: vec![monomial],
-        }
+        Polynomial::default() + monomial
     }
 }
 
 impl std::fmt::Display for Polynomial {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.monomials.is_empty() {
-            write!(f, "0")
-        } else {
-            write!(
-                f,
-                "{}",
-                self.monomials
-                    .iter()
-                    .map(|monomial| monomial.to_string())
-                    .join(" + ")
-            )
+        write!(f, "{}", self.format(&Notation::default()))
+    }
+}
+
+/// Displays a polynomial with all monomials of the same basis grouped together,
+/// e.g. `(a + b) e1 - c e2`.
+/// Bases are in canonical order, i.e. ordered by grade and then lexicographically.
+pub struct Collected<'a, S>(&'a Polynomial, &'a S);
+
+impl<S: Style> std::fmt::Display for Collected<'_, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_zero() {
+            return write!(f, "0");
+        }
+
+        let style = self.1;
+        let monomials = self.0.monomials();
+        let groups = monomials.iter().group_by(|monomial| &monomial.basis);
+
+        let mut terms = Vec::new();
+        for (basis, monomials) in &groups {
+            let monomials: Vec<&Monomial> = monomials.collect();
+            if monomials.len() == 1 || basis.grade() == 0 {
+                terms.extend(
+                    monomials
+                        .into_iter()
+                        .map(|monomial| signed_term(monomial, style)),
+                );
+            } else {
+                // The coefficient of a named blade whose orientation differs changes its sign.
+                let (sign, name) = style.basis(basis);
+                let coefficients = monomials.into_iter().map(|monomial| Monomial {
+                    scalar: sign * monomial.scalar.clone(),
+                    symbols: monomial.symbols.clone(),
+                    basis: Basis::scalar(basis.dimension()),
+                });
+                let coefficient = join_signed_terms(coefficients.map(|c| signed_term(&c, style)));
+                terms.push((false, style.group(&coefficient, &name)));
+            }
+        }
+
+        write!(f, "{}", join_signed_terms(terms.into_iter()))
+    }
+}
+
+/// Splits a monomial into its sign and its magnitude.
+fn signed_term(monomial: &Monomial, style: &impl Style) -> (bool, String) {
+    let (sign, _) = style.basis(&monomial.basis);
+    if (sign * monomial.scalar.clone()).is_negative() {
+        (true, (-monomial.clone()).format(style))
+    } else {
+        (false, monomial.format(style))
+    }
+}
+
+/// Joins terms by `+` and `-` instead of adding negated terms.
+fn join_signed_terms(terms: impl Iterator<Item = (bool, String)>) -> String {
+    let mut result = String::new();
+    for (i, (negative, term)) in terms.enumerate() {
+        match (i, negative) {
+            (0, false) => {}
+            (0, true) => result.push('-'),
+            (_, false) => result.push_str(" + "),
+            (_, true) => result.push_str(" - "),
         }
+        result.push_str(&term);
     }
+    result
 }
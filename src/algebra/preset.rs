@@ -0,0 +1,140 @@
+use super::{
+    metric::{Metric, Square},
+    notation::Notation,
+};
+
+use Square::{Neg, Pos, Zero};
+
+/// Common algebra, selected by its name instead of its metric.
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    squares: &'static [Square],
+    /// Conventional names of the vectors, or none for the default names.
+    vectors: &'static [&'static str],
+    /// Conventional names of blades and their vectors.
+    blades: &'static [(&'static [usize], &'static str)],
+}
+
+impl Preset {
+    pub fn metric(&self) -> Metric {
+        Metric(self.squares.to_vec())
+    }
+
+    pub fn notation(&self) -> Notation {
+        Notation::new(
+            self.vectors.iter().map(|name| name.to_string()).collect(),
+            self.blades
+                .iter()
+                .map(|(vectors, name)| (vectors.to_vec(), name.to_string()))
+                .collect(),
+        )
+    }
+}
+
+pub const PRESETS: [Preset; 13] = [
+    Preset {
+        name: "complex",
+        description: "Complex numbers",
+        squares: &[Neg],
+        vectors: &[],
+        blades: &[],
+    },
+    Preset {
+        name: "hyperbolic",
+        description: "Hyperbolic numbers",
+        squares: &[Pos],
+        vectors: &[],
+        blades: &[],
+    },
+    Preset {
+        name: "dual",
+        description: "Dual numbers",
+        squares: &[Zero],
+        vectors: &[],
+        blades: &[],
+    },
+    Preset {
+        name: "vga2",
+        description: "Vector algebra of the Euclidean plane",
+        squares: &[Pos, Pos],
+        vectors: &["e1", "e2"],
+        blades: &[],
+    },
+    Preset {
+        name: "vga3",
+        description: "Vector algebra of Euclidean space",
+        squares: &[Pos, Pos, Pos],
+        vectors: &["e1", "e2", "e3"],
+        blades: &[],
+    },
+    Preset {
+        name: "sta",
+        description: "Spacetime algebra",
+        squares: &[Pos, Neg, Neg, Neg],
+        vectors: &["γ0", "γ1", "γ2", "γ3"],
+        blades: &[],
+    },
+    Preset {
+        name: "sta-mostly-plus",
+        description: "Spacetime algebra with the mostly plus signature",
+        squares: &[Neg, Pos, Pos, Pos],
+        vectors: &["γ0", "γ1", "γ2", "γ3"],
+        blades: &[],
+    },
+    Preset {
+        name: "cga2",
+        description: "Conformal algebra of the plane, e3 and e4 square to 1 and -1",
+        squares: &[Pos, Pos, Pos, Neg],
+        vectors: &["e1", "e2", "e3", "e4"],
+        blades: &[],
+    },
+    Preset {
+        name: "cga3",
+        description: "Conformal algebra of space, e4 and e5 square to 1 and -1",
+        squares: &[Pos, Pos, Pos, Pos, Neg],
+        vectors: &["e1", "e2", "e3", "e4", "e5"],
+        blades: &[],
+    },
+    Preset {
+        name: "quaternion",
+        description: "Quaternions i, j and k",
+        squares: &[Neg, Neg],
+        vectors: &["i", "j"],
+        blades: &[(&[0, 1], "k")],
+    },
+    Preset {
+        name: "dual-quaternion",
+        description: "Dual quaternions i, j, k, ε and εi = ε i, εj, εk, \
+            within 3D plane-based geometric algebra including its odd grades",
+        squares: &[Pos, Pos, Pos, Zero],
+        vectors: &["e1", "e2", "e3", "e0"],
+        blades: &[
+            (&[2, 1], "i"),
+            (&[0, 2], "j"),
+            (&[1, 0], "k"),
+            (&[3, 0, 1, 2], "ε"),
+            (&[3, 0], "εi"),
+            (&[3, 1], "εj"),
+            (&[3, 2], "εk"),
+        ],
+    },
+    Preset {
+        name: "pauli",
+        description: "Algebra of the Pauli matrices",
+        squares: &[Pos, Pos, Pos],
+        vectors: &["σ1", "σ2", "σ3"],
+        blades: &[],
+    },
+    Preset {
+        name: "dirac",
+        description: "Algebra of the Dirac matrices",
+        squares: &[Pos, Neg, Neg, Neg],
+        vectors: &["γ0", "γ1", "γ2", "γ3"],
+        blades: &[],
+    },
+];
+
+pub fn preset(name: &str) -> Option<&'static Preset> {
+    PRESETS.iter().find(|preset| preset.name == name)
+}
@@ -0,0 +1,98 @@
+use itertools::Itertools;
+use num::{BigRational, One, Signed};
+
+use super::{
+    basis::Basis,
+    monom::{Monomial, Symbol},
+    notation::Notation,
+    sign::Sign,
+};
+
+/// How the parts of monomials and polynomials are written.
+pub trait Style {
+    /// Name of a basis, along with the sign of the named blade relative to the basis.
+    fn basis(&self, basis: &Basis) -> (Sign, String);
+
+    fn scalar(&self, scalar: &BigRational) -> String;
+
+    fn symbol(&self, name: &str, multiplicity: isize) -> String;
+
+    /// Symbolic power of a base and an exponent, which are sums of scalar monomials.
+    fn power(&self, base: &[Monomial], exponent: &[Monomial], multiplicity: isize) -> String;
+
+    /// Separates a coefficient from its basis.
+    fn separator(&self) -> &'static str;
+
+    /// Coefficient of several monomials collected in front of their basis.
+    fn group(&self, coefficient: &str, basis: &str) -> String;
+}
+
+/// Plain text, which is parsed as the same value again.
+impl Style for Notation {
+    fn basis(&self, basis: &Basis) -> (Sign, String) {
+        Notation::basis(self, basis)
+    }
+
+    fn scalar(&self, scalar: &BigRational) -> String {
+        scalar.to_string()
+    }
+
+    fn symbol(&self, name: &str, multiplicity: isize) -> String {
+        match multiplicity {
+            1 => name.to_string(),
+            _ => format!("{name}^{multiplicity}"),
+        }
+    }
+
+    fn power(&self, base: &[Monomial], exponent: &[Monomial], multiplicity: isize) -> String {
+        let factor = |monomials: &[Monomial]| {
+            if is_atomic(monomials) {
+                sum(monomials, self)
+            } else {
+                format!("({})", sum(monomials, self))
+            }
+        };
+        let power = format!("{}^{}", factor(base), factor(exponent));
+        match multiplicity {
+            1 => power,
+            _ => format!("({power})^{multiplicity}"),
+        }
+    }
+
+    fn separator(&self) -> &'static str {
+        " "
+    }
+
+    fn group(&self, coefficient: &str, basis: &str) -> String {
+        format!("({coefficient}) {basis}")
+    }
+}
+
+/// Monomials written as a sum, such as the base of `(1/2 + a)^n`.
+pub fn sum(monomials: &[Monomial], style: &impl Style) -> String {
+    if monomials.is_empty() {
+        return "0".to_string();
+    }
+    monomials
+        .iter()
+        .map(|monomial| monomial.format(style))
+        .join(" + ")
+}
+
+/// Whether monomials are a single natural number or symbol, which needs no parentheses.
+pub fn is_atomic(monomials: &[Monomial]) -> bool {
+    match monomials {
+        [] => true,
+        [monomial] if monomial.symbols.is_empty() => {
+            monomial.scalar.is_integer() && !monomial.scalar.is_negative()
+        }
+        [monomial] => {
+            monomial.scalar.is_one()
+                && monomial.symbols.len() == 1
+                && monomial.symbols.iter().all(|(symbol, &multiplicity)| {
+                    matches!(symbol, Symbol::Name(_)) && multiplicity == 1
+                })
+        }
+        _ => false,
+    }
+}
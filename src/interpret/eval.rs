@@ -1,17 +1,176 @@
-use common_macros::hash_map;
-use num::{BigRational, One, Zero};
+use std::collections::BTreeMap;
+
+use common_macros::b_tree_map;
+use itertools::Itertools;
+use num::{BigInt, BigRational, One, ToPrimitive, Zero};
 
 use crate::algebra::{
-    basis::Basis, metric::Metric, monom::Monomial, polynom::Polynomial, sign::Sign, Product,
+    basis::Basis,
+    metric::Metric,
+    monom::{Monomial, MultiplicityOverflow, Symbol},
+    polynom::{Polynomial, PowerError},
+    sign::Sign,
+    Product,
 };
 
-use crate::parse::span::{Span, Spanned};
+use crate::parse::span::Spanned;
+
+use super::expr::{Binary, Comparison, Expr, Statement, Unary};
+
+/// Cause of an expression being undefined.
+#[derive(Debug, Clone)]
+pub enum Undefined {
+    /// Division by, or negative power of, a multivector without an inverse.
+    NonInvertible,
+    /// Basis referring to vectors the metric does not have.
+    BasisOutOfRange {
+        vectors: Vec<usize>,
+        dimension: usize,
+    },
+    /// Exponent which is a ratio but not an integer.
+    NonIntegralExponent(BigRational),
+    /// Integral exponent too large to be evaluated.
+    ExponentOutOfRange(BigInt),
+    /// Exponent with a non-scalar basis.
+    NonScalarExponent,
+    /// Symbolic exponent of a non-scalar base, whose power does not commute like a scalar.
+    NonScalarBase,
+    /// Power of a base with several terms, which has too many terms to be evaluated.
+    TooManyTerms,
+    /// Number literal which is not a valid ratio, such as `1/0`, or which is too large.
+    InvalidNumber(String),
+    /// Explicit `_|_`.
+    Bottom,
+    /// Variable assigned before the metric changed to another dimension.
+    ForeignVariable { name: String, dimension: usize },
+    /// Previous result of an algebra of another dimension, which `:embed` leaves as it is.
+    ForeignResult { name: String, dimension: usize },
+    /// Previous result which does not exist yet.
+    MissingResult(String),
+}
+
+impl std::fmt::Display for Undefined {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Undefined::NonInvertible => write!(f, "Not invertible, since its norm vanishes"),
+            Undefined::BasisOutOfRange { vectors, dimension } => {
+                let basis = vectors.iter().map(|vector| vector.to_string()).join("");
+                let available = match dimension {
+                    0 => "this algebra has no vectors".to_string(),
+                    1 => "this algebra only has the vector e0".to_string(),
+                    _ => format!("this algebra only has vectors e0 to e{}", dimension - 1),
+                };
+                if vectors.len() > 1 {
+                    let product = vectors.iter().map(|vector| format!("e{vector}")).join(" ");
+                    write!(f, "e{basis} is read as {product}, but {available}")
+                } else {
+                    write!(f, "e{basis} does not exist, {available}")
+                }
+            }
+            Undefined::NonIntegralExponent(exponent) => {
+                write!(f, "The exponent {exponent} is not an integer")
+            }
+            Undefined::ExponentOutOfRange(exponent) => {
+                write!(f, "The exponent {exponent} is out of range")
+            }
+            Undefined::NonScalarExponent => write!(f, "Exponents must be scalars"),
+            Undefined::NonScalarBase => {
+                write!(f, "Only scalars can be raised to a symbolic exponent")
+            }
+            Undefined::TooManyTerms => write!(f, "The power has too many terms to be evaluated"),
+            Undefined::InvalidNumber(number) if number.contains('/') => {
+                write!(f, "{number} has a zero denominator")
+            }
+            Undefined::InvalidNumber(number) => write!(f, "{number} is out of range"),
+            Undefined::Bottom => write!(f, "Explicitly undefined"),
+            Undefined::ForeignVariable { name, dimension } => write!(
+                f,
+                "{name} was assigned in an algebra of {dimension} dimensions"
+            ),
+            Undefined::ForeignResult { name, dimension } => write!(
+                f,
+                "{name} is a result of an algebra of {dimension} dimensions"
+            ),
+            Undefined::MissingResult(name) if name == "ans" => {
+                write!(f, "There is no previous result yet")
+            }
+            Undefined::MissingResult(name) => write!(f, "There is no result {name} yet"),
+        }
+    }
+}
+
+/// Names of previous results, the last one `ans` and all of them numbered `$1`, `$2`, ...
+pub fn is_result(name: &str) -> bool {
+    name == "ans" || name.starts_with('$')
+}
+
+pub enum Value {
+    Polynomial(Polynomial),
+    Boolean(bool),
+    Assertion(bool),
+    Assignment(String, Polynomial),
+}
+
+/// Values bound to names by assignments.
+/// Names which are not bound remain symbols.
+pub type Variables = BTreeMap<String, Polynomial>;
+
+pub fn exec(
+    statement: Statement,
+    metric: &Metric,
+    variables: &mut Variables,
+) -> Result<Value, Vec<Spanned<Undefined>>> {
+    match statement {
+        Statement::Expr(expr) => Ok(Value::Polynomial(eval(expr, metric, variables)?)),
+        Statement::Compare(comparison, lhs, rhs) => Ok(Value::Boolean(compare(
+            comparison, lhs, rhs, metric, variables,
+        )?)),
+        Statement::Assert(comparison, lhs, rhs) => Ok(Value::Assertion(compare(
+            comparison, lhs, rhs, metric, variables,
+        )?)),
+        Statement::Assign(name, expr) => {
+            let value = eval(expr, metric, variables)?;
+            variables.insert(name.clone(), value.clone());
+            Ok(Value::Assignment(name, value))
+        }
+    }
+}
 
-use super::expr::{Binary, Expr, Unary};
+/// Two expressions are equal if their difference simplifies to zero.
+fn compare(
+    comparison: Comparison,
+    lhs: Spanned<Expr>,
+    rhs: Spanned<Expr>,
+    metric: &Metric,
+    variables: &Variables,
+) -> Result<bool, Vec<Spanned<Undefined>>> {
+    let (lhs, rhs) = both(eval(lhs, metric, variables), eval(rhs, metric, variables))?;
+    let equal = (lhs + -rhs).is_zero();
+    Ok(match comparison {
+        Comparison::Equal => equal,
+        Comparison::NotEqual => !equal,
+    })
+}
 
-pub struct Undefined(pub Vec<Span>);
+/// Combines two results, collecting the causes of both if undefined.
+fn both<T>(
+    lhs: Result<T, Vec<Spanned<Undefined>>>,
+    rhs: Result<T, Vec<Spanned<Undefined>>>,
+) -> Result<(T, T), Vec<Spanned<Undefined>>> {
+    match (lhs, rhs) {
+        (Err(mut lhs), Err(rhs)) => {
+            lhs.extend(rhs);
+            Err(lhs)
+        }
+        (lhs, rhs) => Ok((lhs?, rhs?)),
+    }
+}
 
-pub fn eval(expr: Spanned<Expr>, metric: &Metric) -> Result<Polynomial, Undefined> {
+pub fn eval(
+    expr: Spanned<Expr>,
+    metric: &Metric,
+    variables: &Variables,
+) -> Result<Polynomial, Vec<Spanned<Undefined>>> {
     let dimension = metric.dimension();
     let span = expr.1;
 
@@ -31,10 +190,11 @@ pub fn eval(expr: Spanned<Expr>, metric: &Metric) -> Result<Polynomial, Undefine
         .into()),
 
         Expr::Basis(vectors) => {
-            for &vector in &vectors {
-                if vector >= dimension {
-                    return Err(Undefined(vec![span]));
-                }
+            if vectors.iter().any(|&vector| vector >= dimension) {
+                return Err(vec![Spanned(
+                    Undefined::BasisOutOfRange { vectors, dimension },
+                    span,
+                )]);
             }
             if let Some((sign, basis)) = vectors
                 .into_iter()
@@ -68,18 +228,9 @@ pub fn eval(expr: Spanned<Expr>, metric: &Metric) -> Result<Polynomial, Undefine
         }
 
         Expr::Binary(binary, lhs, rhs) => {
-            let lhs = eval(*lhs, metric);
-            let rhs = eval(*rhs, metric);
-
-            let (lhs, rhs) = match (lhs, rhs) {
-                (Err(Undefined(mut lhs)), Err(Undefined(rhs))) => {
-                    lhs.extend(rhs);
-                    return Err(Undefined(lhs));
-                }
-                (lhs, rhs) => (lhs?, rhs?),
-            };
+            let (lhs, rhs) = both(eval(*lhs, metric, variables), eval(*rhs, metric, variables))?;
 
-            Ok(match binary {
+            let result = match binary {
                 Binary::Geometric => lhs.product(Product::Geometric, rhs, metric),
                 Binary::Exterior => lhs.product(Product::Exterior, rhs, metric),
                 Binary::Regressive => lhs.product(Product::Regressive, rhs, metric),
@@ -89,31 +240,68 @@ pub fn eval(expr: Spanned<Expr>, metric: &Metric) -> Result<Polynomial, Undefine
                 Binary::Scalar => lhs.product(Product::Scalar, rhs, metric),
                 Binary::Divide => match rhs.clone().inverse(metric) {
                     Some(rhs) => lhs.product(Product::Geometric, rhs, metric),
-                    None => return Err(Undefined(vec![span])),
+                    None => return Err(vec![Spanned(Undefined::NonInvertible, span)]),
                 },
-                Binary::Add => lhs + rhs,
-                Binary::Sub => lhs + -rhs,
+                Binary::Add => Ok(lhs + rhs),
+                Binary::Sub => Ok(lhs + -rhs),
+            };
+            result.map_err(|MultiplicityOverflow(multiplicity)| {
+                vec![Spanned(Undefined::ExponentOutOfRange(multiplicity), span)]
             })
         }
 
         Expr::Unary(unary, x) => {
-            let x = eval(*x, metric)?;
+            let x = eval(*x, metric, variables)?;
             match unary {
                 Unary::Neg => Ok(-x),
                 Unary::Dual => Ok(x.dual()),
                 Unary::Reverse => Ok(x.reverse()),
-                Unary::Inverse => x.clone().inverse(metric).ok_or(Undefined(vec![span])),
+                Unary::Inverse => x
+                    .clone()
+                    .inverse(metric)
+                    .ok_or_else(|| vec![Spanned(Undefined::NonInvertible, span)]),
                 Unary::Involution => Ok(x.involute()),
                 Unary::Conjugate => Ok(x.conjugate()),
             }
         }
 
-        Expr::Power(base, exponent) => Ok(eval(*base, metric)?
-            .power(exponent, metric)
-            .ok_or(Undefined(vec![span]))?),
+        Expr::Power(base, exponent) => {
+            let base_span = base.1.clone();
+            let exponent_span = exponent.1.clone();
+            let (base, exponent) = both(
+                eval(*base, metric, variables),
+                eval(*exponent, metric, variables),
+            )?;
+            match exponent.scalar() {
+                Some(exponent) => {
+                    let exponent = integral_exponent(exponent)
+                        .map_err(|cause| vec![Spanned(cause, exponent_span.clone())])?;
+                    base.power(exponent, metric).map_err(|error| {
+                        vec![match error {
+                            PowerError::NonInvertible => Spanned(Undefined::NonInvertible, span),
+                            PowerError::TooManyTerms => Spanned(Undefined::TooManyTerms, span),
+                            PowerError::TooLarge => Spanned(
+                                Undefined::ExponentOutOfRange(exponent.into()),
+                                exponent_span,
+                            ),
+                            PowerError::Overflow(MultiplicityOverflow(multiplicity)) => {
+                                Spanned(Undefined::ExponentOutOfRange(multiplicity), span)
+                            }
+                        }]
+                    })
+                }
+                None if !exponent.is_scalar() => {
+                    Err(vec![Spanned(Undefined::NonScalarExponent, exponent_span)])
+                }
+                None if !base.is_scalar() => {
+                    Err(vec![Spanned(Undefined::NonScalarBase, base_span)])
+                }
+                None => Ok(symbolic_power(base, exponent, dimension)),
+            }
+        }
 
         Expr::Norm(x) => {
-            let x = eval(*x, metric)?;
+            let x = eval(*x, metric, variables)?;
             let norm = x.norm(metric);
             Ok(Monomial {
                 scalar: norm,
@@ -123,13 +311,67 @@ pub fn eval(expr: Spanned<Expr>, metric: &Metric) -> Result<Polynomial, Undefine
             .into())
         }
 
+        Expr::Grade(x, grade) => Ok(eval(*x, metric, variables)?.grade_projection(grade)),
+
+        Expr::Unknown(name) if variables.contains_key(&name) => {
+            let value = &variables[&name];
+            match value.dimension() {
+                Some(other) if other != dimension && is_result(&name) => Err(vec![Spanned(
+                    Undefined::ForeignResult {
+                        name,
+                        dimension: other,
+                    },
+                    span,
+                )]),
+                Some(other) if other != dimension => Err(vec![Spanned(
+                    Undefined::ForeignVariable {
+                        name,
+                        dimension: other,
+                    },
+                    span,
+                )]),
+                _ => Ok(value.clone()),
+            }
+        }
+
+        Expr::Unknown(name) if is_result(&name) => {
+            Err(vec![Spanned(Undefined::MissingResult(name), span)])
+        }
+
         Expr::Unknown(name) => Ok(Monomial {
             scalar: BigRational::one(),
-            symbols: hash_map![name => 1],
+            symbols: b_tree_map![Symbol::Name(name) => 1],
             basis: Basis::scalar(dimension),
         }
         .into()),
 
-        Expr::Bottom => Err(Undefined(vec![span])),
+        Expr::InvalidNumber(number) => Err(vec![Spanned(Undefined::InvalidNumber(number), span)]),
+
+        Expr::Bottom => Err(vec![Spanned(Undefined::Bottom, span)]),
+    }
+}
+
+/// Bounds the exponents of powers, whose coefficients would otherwise grow without limit.
+const MAX_EXPONENT: isize = 1 << 16;
+
+fn integral_exponent(exponent: BigRational) -> Result<isize, Undefined> {
+    if !exponent.is_integer() {
+        return Err(Undefined::NonIntegralExponent(exponent));
+    }
+    let exponent = exponent.to_integer();
+    match exponent.to_isize() {
+        Some(n) if n.abs() <= MAX_EXPONENT => Ok(n),
+        _ => Err(Undefined::ExponentOutOfRange(exponent)),
+    }
+}
+
+/// Power with a symbolic exponent, which is left unevaluated as a symbol such as `a^n`.
+/// Only scalars have such powers, since they commute with everything like a symbol does.
+fn symbolic_power(base: Polynomial, exponent: Polynomial, dimension: usize) -> Polynomial {
+    Monomial {
+        scalar: BigRational::one(),
+        symbols: b_tree_map![Symbol::power(base.monomials(), exponent.monomials()) => 1],
+        basis: Basis::scalar(dimension),
     }
+    .into()
 }
@@ -1,4 +1,7 @@
-use crate::parse::span::Spanned;
+use crate::{
+    algebra::{latex::Latex, style::Style},
+    parse::span::Spanned,
+};
 
 use num::BigRational;
 
@@ -8,11 +11,28 @@ pub enum Expr {
     Pseudoscalar,
     Basis(Vec<usize>),
     Unknown(String),
+    InvalidNumber(String),
     Bottom,
     Binary(Binary, Box<Spanned<Expr>>, Box<Spanned<Expr>>),
-    Power(Box<Spanned<Expr>>, isize),
+    Power(Box<Spanned<Expr>>, Box<Spanned<Expr>>),
     Unary(Unary, Box<Spanned<Expr>>),
     Norm(Box<Spanned<Expr>>),
+    /// Part of the given grade, `\langle a \rangle_k` in LaTeX.
+    Grade(Box<Spanned<Expr>>, usize),
+}
+
+#[derive(Debug, Clone)]
+pub enum Statement {
+    Expr(Spanned<Expr>),
+    Compare(Comparison, Spanned<Expr>, Spanned<Expr>),
+    Assert(Comparison, Spanned<Expr>, Spanned<Expr>),
+    Assign(String, Spanned<Expr>),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -38,3 +58,100 @@ pub enum Unary {
     Involution,
     Conjugate,
 }
+
+/// Operands of products, powers and the like are parenthesized,
+/// while fractions and norms delimit their operands themselves.
+const ATOM: u8 = 5;
+
+impl Expr {
+    /// LaTeX of the expression as it was written, e.g. `\tilde{R} \wedge \mathbf{e}_{31}`.
+    pub fn latex(&self, latex: &Latex) -> String {
+        match self {
+            Expr::Number(number) => latex.scalar(number),
+            Expr::Pseudoscalar => "I".to_string(),
+            Expr::Basis(vectors) if vectors.is_empty() => "1".to_string(),
+            Expr::Basis(vectors) => latex.vectors(vectors),
+            Expr::Unknown(name) => latex.symbol(name, 1),
+            Expr::InvalidNumber(number) => number.clone(),
+            Expr::Bottom => r"\bot".to_string(),
+            Expr::Binary(Binary::Divide, lhs, rhs) => {
+                format!(r"\frac{{{}}}{{{}}}", lhs.0.latex(latex), rhs.0.latex(latex))
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let precedence = self.precedence();
+                let lhs = lhs.0.operand(latex, precedence, false);
+                let rhs = rhs.0.operand(latex, precedence, true);
+                let op = match op {
+                    Binary::Geometric => "",
+                    Binary::Exterior => r"\wedge ",
+                    Binary::Regressive => r"\vee ",
+                    Binary::LeftContraction => r"\rfloor ",
+                    Binary::RightContraction => r"\lfloor ",
+                    Binary::Inner => r"\cdot ",
+                    Binary::Scalar => "* ",
+                    Binary::Divide => unreachable!(),
+                    Binary::Add => "+ ",
+                    Binary::Sub => "- ",
+                };
+                format!("{lhs} {op}{rhs}")
+            }
+            Expr::Power(base, exponent) => base.0.superscript(latex, &exponent.0.latex(latex)),
+            Expr::Unary(op, x) => {
+                let accent = |narrow: &str, wide: &str| match x.0.precedence() {
+                    ATOM => format!(r"\{narrow}{{{}}}", x.0.latex(latex)),
+                    _ => format!(r"\{wide}{{{}}}", x.0.latex(latex)),
+                };
+                match op {
+                    Unary::Neg => format!("-{}", x.0.operand(latex, self.precedence(), true)),
+                    Unary::Dual => x.0.superscript(latex, "*"),
+                    Unary::Reverse => accent("tilde", "widetilde"),
+                    Unary::Inverse => x.0.superscript(latex, "-1"),
+                    Unary::Involution => accent("hat", "widehat"),
+                    Unary::Conjugate => accent("bar", "overline"),
+                }
+            }
+            Expr::Norm(x) => format!(r"\left\lVert {} \right\rVert", x.0.latex(latex)),
+            Expr::Grade(x, grade) => format!(
+                r"\left\langle {} \right\rangle_{{{grade}}}",
+                x.0.latex(latex)
+            ),
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Binary(Binary::Add | Binary::Sub, _, _) => 1,
+            Expr::Binary(Binary::Divide, _, _) => ATOM,
+            Expr::Binary(Binary::Geometric, _, _) => 3,
+            Expr::Binary(_, _, _) => 2,
+            Expr::Unary(Unary::Neg, _) => 4,
+            _ => ATOM,
+        }
+    }
+
+    /// LaTeX of an operand raised to a superscript, such as `a^{2}`.
+    /// Operands with a superscript of their own are braced, e.g. `{a^{2}}^{3}`,
+    /// since LaTeX does not allow double superscripts.
+    fn superscript(&self, latex: &Latex, superscript: &str) -> String {
+        let operand = self.operand(latex, ATOM, true);
+        match self {
+            Expr::Power(_, _) | Expr::Unary(Unary::Dual | Unary::Inverse, _) => {
+                format!("{{{operand}}}^{{{superscript}}}")
+            }
+            _ => format!("{operand}^{{{superscript}}}"),
+        }
+    }
+
+    /// LaTeX of an operand, parenthesized unless it binds more tightly than its operator.
+    /// Operators are left-associative, so that right operands are parenthesized on a par.
+    /// Negations are only parenthesized after other operands, since they commute with products.
+    fn operand(&self, latex: &Latex, precedence: u8, right: bool) -> String {
+        let own = self.precedence();
+        let negation = matches!(self, Expr::Unary(Unary::Neg, _));
+        if own < precedence || (right && own == precedence && own != ATOM) || (right && negation) {
+            format!(r"\left({}\right)", self.latex(latex))
+        } else {
+            self.latex(latex)
+        }
+    }
+}
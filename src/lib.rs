@@ -0,0 +1,7 @@
+pub mod algebra;
+pub mod interpret;
+pub mod parse;
+pub mod repl;
+
+#[cfg(test)]
+mod test;
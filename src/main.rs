@@ -1,11 +1,3 @@
-pub mod algebra;
-pub mod interpret;
-pub mod parse;
-pub mod repl;
-
-#[cfg(test)]
-mod test;
-
-fn main() {
-    repl::repl();
+fn main() -> std::process::ExitCode {
+    galc::repl::repl()
 }
@@ -1,58 +1,321 @@
+// The error type of chumsky's `select!` closures is out of our control.
+#![allow(clippy::result_large_err)]
+
+pub mod error;
+mod latex;
 pub mod span;
 mod token;
 
+#[cfg(test)]
+mod test;
+
 use chumsky::prelude::*;
+use itertools::Itertools;
+use num::{BigInt, BigRational};
+use serde::Deserialize;
 
-use crate::interpret::expr::{Binary, Expr, Unary};
-
-use self::{span::Spanned, token::Token};
-
-pub fn parse(string: &str) -> Option<Spanned<Expr>> {
-    match token::tokenize(string) {
-        Ok(spanned_tokens) => {
-            let tokens: Vec<Token> = spanned_tokens
-                .iter()
-                .map(|Spanned(item, _)| item)
-                .cloned()
-                .collect();
-
-            match expr_parser().parse(tokens) {
-                Ok(expr) => Some(span::translate_spans(expr, &spanned_tokens)),
-                Err(_) => {
-                    println!("Syntax error");
-                    None
-                }
-            }
+use crate::{
+    algebra::notation::Notation,
+    interpret::{
+        eval,
+        expr::{Binary, Comparison, Expr, Statement, Unary},
+    },
+};
+
+use self::{error::SyntaxError, span::Spanned, token::Token};
+
+/// Parses a single statement in the default notation.
+pub fn parse(string: &str) -> Result<Statement, Vec<SyntaxError>> {
+    let spanned_tokens = tokenize(string, &Notation::default(), Syntax::Plain)?;
+    match statement_parser()
+        .then_ignore(end())
+        .parse(tokens(&spanned_tokens))
+    {
+        Ok(statement) => Ok(span::translate_statement_spans(statement, &spanned_tokens)),
+        Err(errors) => Err(syntax_errors(
+            errors,
+            &spanned_tokens,
+            string,
+            Syntax::Plain,
+        )),
+    }
+}
+
+/// Parses statements separated by `;`. Empty statements are skipped.
+/// Bases are named according to the notation.
+pub fn parse_statements(
+    string: &str,
+    notation: &Notation,
+    syntax: Syntax,
+) -> Result<Vec<Statement>, Vec<SyntaxError>> {
+    let spanned_tokens = tokenize(string, notation, syntax)?;
+    let parser = statement_parser()
+        .or_not()
+        .separated_by(just(Token::Semicolon))
+        .then_ignore(end())
+        .map(|statements| statements.into_iter().flatten());
+    match parser.parse(tokens(&spanned_tokens)) {
+        Ok(statements) => Ok(statements
+            .map(|statement| span::translate_statement_spans(statement, &spanned_tokens))
+            .collect()),
+        Err(errors) => Err(syntax_errors(errors, &spanned_tokens, string, syntax)),
+    }
+}
+
+/// How statements are written.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "String")]
+pub enum Syntax {
+    /// galc's own notation, such as `e12 ^ a`.
+    Plain,
+    /// LaTeX formulas, such as `\mathbf{e}_{12} \wedge a`.
+    Latex,
+}
+
+impl Syntax {
+    /// The syntax of an input, where inputs starting with a backslash are LaTeX regardless.
+    pub fn of(self, string: &str) -> Syntax {
+        if string.trim_start().starts_with('\\') {
+            Syntax::Latex
+        } else {
+            self
         }
-        Err(_) => {
-            println!("Syntax error");
-            None
+    }
+}
+
+impl std::str::FromStr for Syntax {
+    type Err = String;
+
+    fn from_str(syntax: &str) -> Result<Syntax, String> {
+        match syntax {
+            "plain" => Ok(Syntax::Plain),
+            "latex" => Ok(Syntax::Latex),
+            _ => Err(format!(
+                "{syntax} is not an input syntax, use plain or latex"
+            )),
         }
     }
 }
 
-fn expr_parser<'a>() -> impl Parser<Token, Spanned<Expr>, Error = Simple<Token>> + Clone + 'a {
-    recursive(|expr| binary_parser(expr.clone()))
-        .padded_by(just(Token::Whitespace).repeated())
-        .then_ignore(end())
+impl TryFrom<String> for Syntax {
+    type Error = String;
+
+    fn try_from(syntax: String) -> Result<Syntax, String> {
+        syntax.parse()
+    }
+}
+
+impl std::fmt::Display for Syntax {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Syntax::Plain => write!(f, "plain"),
+            Syntax::Latex => write!(f, "latex"),
+        }
+    }
+}
+
+/// Hints at operators of plain inputs whose spacing suggests another reading than the parsed one,
+/// e.g. `a -b` is a subtraction rather than the product of `a` and `-b`.
+pub fn spacing_hints(string: &str) -> Vec<Spanned<String>> {
+    let Ok(tokens) = token::tokenize(string) else {
+        return Vec::new();
+    };
+    let ends_operand = |token: &Token| {
+        matches!(
+            token,
+            Token::Number(_)
+                | Token::Basis(_)
+                | Token::Identifier(_)
+                | Token::Bottom
+                | Token::ParenClose
+                | Token::BracketClose
+                | Token::Dagger
+        )
+    };
+    let starts_operand = |token: &Token| {
+        matches!(
+            token,
+            Token::Number(_)
+                | Token::Basis(_)
+                | Token::Identifier(_)
+                | Token::Bottom
+                | Token::ParenOpen
+                | Token::BracketOpen
+        )
+    };
+
+    let mut hints = Vec::new();
+    for (i, Spanned(operator, span)) in tokens.iter().enumerate() {
+        let before = |n: usize| i.checked_sub(n).map(|j| &tokens[j].0);
+        let Some(Spanned(next, _)) = tokens.get(i + 1) else {
+            continue;
+        };
+        // Operators separated from the operand before them but attached to the one after them.
+        let prefix = matches!(before(1), Some(Token::Whitespace))
+            && before(2).is_some_and(ends_operand)
+            && starts_operand(next);
+        let hint = match operator {
+            Token::Minus if prefix => "`-` is a subtraction here, write `a (-b)` for a product",
+            Token::Asteriks if prefix => {
+                "`*` is a scalar product here, write `a (*b)` for a product with a dual"
+            }
+            Token::Hat if prefix => "`^` is a power here, write `a (^b)` for a product",
+            Token::RightContraction
+                if string.chars().skip(span.start).take(2).eq("|-".chars())
+                    && starts_operand(next) =>
+            {
+                "`|-` is a right contraction, write `a | -b` for an inner product"
+            }
+            _ => continue,
+        };
+        hints.push(Spanned(hint.to_string(), span.clone()));
+    }
+    hints
+}
+
+fn tokenize(
+    string: &str,
+    notation: &Notation,
+    syntax: Syntax,
+) -> Result<Vec<Spanned<Token>>, Vec<SyntaxError>> {
+    let spanned_tokens = match syntax {
+        Syntax::Plain => token::tokenize(string),
+        Syntax::Latex => latex::tokenize(string),
+    }
+    .map_err(|errors| {
+        errors
+            .into_iter()
+            .map(SyntaxError::from_char_error)
+            .collect::<Vec<_>>()
+    })?;
+
+    // Whitespace and comments only separate tokens, the grammar does not depend on them.
+    let (spanned_tokens, errors): (Vec<_>, Vec<_>) = spanned_tokens
+        .into_iter()
+        .filter(|token| !matches!(token.0, Token::Whitespace | Token::Comment))
+        .map(|token| name_bases(token, notation))
+        .partition(Result::is_ok);
+
+    if errors.is_empty() {
+        Ok(spanned_tokens.into_iter().map(Result::unwrap).collect())
+    } else {
+        Err(errors.into_iter().map(Result::unwrap_err).collect())
+    }
+}
+
+/// Replaces names of bases by the indices of their vectors.
+fn name_bases(token: Spanned<Token>, notation: &Notation) -> Result<Spanned<Token>, SyntaxError> {
+    let Spanned(token, span) = token;
+    let token = match token {
+        Token::Basis(digits) => match notation.digits(&digits) {
+            Some(vectors) => Token::Basis(vectors),
+            None => {
+                return Err(SyntaxError {
+                    message: format!("e{} is not a basis of this algebra", digits.iter().join("")),
+                    hint: Some(format!(
+                        "Its vectors are {}",
+                        (0..notation.dimension())
+                            .map(|i| notation.vector(i))
+                            .join(", ")
+                    )),
+                    span,
+                })
+            }
+        },
+        Token::Identifier(name) => match notation.resolve(&name) {
+            Some(vectors) => Token::Basis(vectors),
+            None => Token::Identifier(name),
+        },
+        token => token,
+    };
+    Ok(Spanned(token, span))
+}
+
+fn tokens(spanned_tokens: &[Spanned<Token>]) -> Vec<Token> {
+    spanned_tokens
+        .iter()
+        .map(|Spanned(item, _)| item)
+        .cloned()
+        .collect()
+}
+
+fn syntax_errors(
+    errors: Vec<Simple<Token>>,
+    spanned_tokens: &[Spanned<Token>],
+    string: &str,
+    syntax: Syntax,
+) -> Vec<SyntaxError> {
+    errors
+        .into_iter()
+        .map(|error| SyntaxError::from_token_error(error, spanned_tokens, string, syntax))
+        .collect()
+}
+
+fn statement_parser<'a>() -> impl Parser<Token, Statement, Error = Simple<Token>> + Clone + 'a {
+    let comparison = expr_parser()
+        .then(
+            select! {
+                Token::Equal => Comparison::Equal,
+                Token::NotEqual => Comparison::NotEqual,
+            }
+            .map_err(|error| expecting(error, [Token::Equal, Token::NotEqual])),
+        )
+        .then(expr_parser())
+        .boxed();
+
+    let assertion = just(Token::Identifier(ASSERT.to_string()))
+        .ignore_then(comparison.clone())
+        .map(|((lhs, comparison), rhs)| Statement::Assert(comparison, lhs, rhs));
+
+    let comparison =
+        comparison.map(|((lhs, comparison), rhs)| Statement::Compare(comparison, lhs, rhs));
+
+    let assignment = select! {
+        Token::Identifier(name) if !RESERVED.contains(&name.as_str()) && !eval::is_result(&name) => name,
+    }
+    .then_ignore(just(Token::Assign))
+    .then(expr_parser())
+    .map(|(name, expr)| Statement::Assign(name, expr));
+
+    assertion
+        .or(assignment)
+        .or(comparison)
+        .or(expr_parser().map(Statement::Expr))
         .boxed()
 }
 
+/// Identifiers with a meaning of their own, which cannot be assigned to.
+pub const RESERVED: [&str; 3] = ["i", ASSERT, "ans"];
+
+/// Keyword of assertions, which must be followed by a comparison.
+const ASSERT: &str = "assert";
+
+fn expr_parser<'a>() -> impl Parser<Token, Spanned<Expr>, Error = Simple<Token>> + Clone + 'a {
+    recursive(|expr| binary_parser(expr.clone())).boxed()
+}
+
 fn operand_parser<'a>(
     expr: impl Parser<Token, Spanned<Expr>, Error = Simple<Token>> + Clone + 'a,
 ) -> impl Parser<Token, Spanned<Expr>, Error = Simple<Token>> + Clone + 'a {
     select! {
-        Token::Number(number) => {
-            match number.parse() {
-                Ok(number) => Expr::Number(number),
-                Err(_) => Expr::Bottom
-            }
-        },
+        Token::Number(number) => number_expr(number),
         Token::Basis(basis) => Expr::Basis(basis),
         Token::Identifier(identifier) if identifier == "i" => Expr::Pseudoscalar,
-        Token::Identifier(identifier) => Expr::Unknown(identifier),
+        // `assert` only starts assertions, so that a mistyped one is not read as a product.
+        Token::Identifier(identifier) if identifier != ASSERT => Expr::Unknown(identifier),
         Token::Bottom => Expr::Bottom,
     }
+    .map_err(|error| {
+        expecting(
+            error,
+            [
+                Token::Number(String::new()),
+                Token::Basis(Vec::new()),
+                Token::Identifier(String::new()),
+                Token::Bottom,
+            ],
+        )
+    })
     .map_with_span(Spanned)
     .or(expr
         .clone()
@@ -62,59 +325,93 @@ fn operand_parser<'a>(
         .delimited_by(just(Token::BracketOpen), just(Token::BracketClose))
         .map(|expr| Expr::Norm(Box::new(expr)))
         .map_with_span(Spanned))
+    // Grade projections are only written in LaTeX, `\langle a \rangle_k`.
+    .or(just(Token::AngleOpen)
+        .ignore_then(expr)
+        .then(
+            select! { Token::AngleClose(grade) => grade }
+                .map_err(|error| expecting(error, [Token::AngleClose(0)])),
+        )
+        .map(|(expr, grade)| Expr::Grade(Box::new(expr), grade))
+        .map_with_span(Spanned))
     .boxed()
 }
 
+/// `select!` does not report what it would have accepted, so the expected tokens are added.
+fn expecting(error: Simple<Token>, tokens: impl IntoIterator<Item = Token>) -> Simple<Token> {
+    let expected = Simple::expected_input_found(
+        error.span(),
+        tokens.into_iter().map(Some),
+        error.found().cloned(),
+    );
+    error.merge(expected)
+}
+
+fn number_expr(number: String) -> Expr {
+    match parse_number(&number) {
+        Some(parsed) => Expr::Number(parsed),
+        None => Expr::InvalidNumber(number),
+    }
+}
+
+/// Parses integers, ratios such as `1/3` and decimals such as `0.25`, `.5` or `1.5e-3` exactly.
+fn parse_number(literal: &str) -> Option<BigRational> {
+    if literal.contains('/') {
+        return literal.parse().ok();
+    }
+
+    let (mantissa, exponent) = match literal.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().ok()?),
+        None => (literal, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits: BigInt = format!("{integer}{fraction}").parse().ok()?;
+    let exponent = exponent.checked_sub(fraction.len().try_into().ok()?)?;
+    if exponent.unsigned_abs() > MAX_DECIMAL_EXPONENT {
+        return None;
+    }
+
+    let ten = BigRational::from_integer(BigInt::from(10));
+    Some(BigRational::from_integer(digits) * ten.pow(exponent))
+}
+
+/// Bounds the size of numbers written in scientific notation.
+const MAX_DECIMAL_EXPONENT: u32 = 4096;
+
 fn binary_parser<'a>(
     expr: impl Parser<Token, Spanned<Expr>, Error = Simple<Token>> + Clone + 'a,
 ) -> impl Parser<Token, Spanned<Expr>, Error = Simple<Token>> + Clone + 'a {
-    let binary = select! {
-        Token::Minus => Unary::Neg,
-        Token::Asteriks => Unary::Dual,
+    let operand = operand_parser(expr.clone());
+
+    let unary = recursive(|unary| {
+        select! {
+            Token::Minus => Unary::Neg,
+            Token::Asteriks => Unary::Dual,
+            Token::Tilde => Unary::Reverse,
+            Token::Excl => Unary::Conjugate,
+            Token::Hat => Unary::Involution,
+        }
+        .map_with_span(Spanned)
+        .then(unary)
+        .map(fold_unary)
+        .or(operand.clone())
+    })
+    .boxed();
+
+    // Operands of a juxtaposition must not start with `-`, `*` or `^`,
+    // since these are read as subtraction, scalar product and power instead.
+    let juxtaposed = select! {
         Token::Tilde => Unary::Reverse,
         Token::Excl => Unary::Conjugate,
-        Token::Hat => Unary::Involution,
     }
     .map_with_span(Spanned)
-    .repeated()
-    .then(operand_parser(expr))
-    .foldr(|op, rhs| {
-        Spanned(
-            Expr::Unary(op.0, Box::new(rhs.clone())),
-            op.1.start..rhs.1.end,
-        )
-    })
+    .then(unary.clone())
+    .map(fold_unary)
+    .or(operand)
     .boxed();
 
-    let binary = binary
-        .clone()
-        .then(
-            just(Token::Hat)
-                .ignore_then(
-                    just(Token::Minus)
-                        .repeated()
-                        .then(select! { Token::Number(n) => n.parse().unwrap() })
-                        .foldr(|_, n: isize| -n),
-                )
-                .map_with_span(Spanned)
-                .repeated(),
-        )
-        .foldl(|lhs, rhs| {
-            Spanned(
-                Expr::Power(Box::new(lhs.clone()), rhs.0),
-                lhs.1.start..rhs.1.end,
-            )
-        })
-        .boxed();
-
-    let binary = binary
-        .clone()
-        .then(
-            just(Token::Whitespace)
-                .or_not()
-                .ignore_then(binary)
-                .repeated(),
-        )
+    let binary = power_parser(unary, expr.clone())
+        .then(power_parser(juxtaposed, expr).repeated())
         .foldl(|lhs, rhs| {
             Spanned(
                 Expr::Binary(
@@ -130,19 +427,17 @@ fn binary_parser<'a>(
     let binary = binary
         .clone()
         .then(
-            just(Token::Whitespace)
-                .ignore_then(select! {
-                    Token::Wedge => Binary::Exterior,
-                    Token::AntiWedge => Binary::Regressive,
-                    Token::LeftContraction => Binary::LeftContraction,
-                    Token::RightContraction => Binary::RightContraction,
-                    Token::InnerProduct => Binary::Inner,
-                    Token::Asteriks => Binary::Scalar,
-                    Token::Solidus => Binary::Divide,
-                })
-                .then_ignore(just(Token::Whitespace))
-                .then(binary)
-                .repeated(),
+            select! {
+                Token::Wedge => Binary::Exterior,
+                Token::AntiWedge => Binary::Regressive,
+                Token::LeftContraction => Binary::LeftContraction,
+                Token::RightContraction => Binary::RightContraction,
+                Token::InnerProduct => Binary::Inner,
+                Token::Asteriks => Binary::Scalar,
+                Token::Solidus => Binary::Divide,
+            }
+            .then(binary)
+            .repeated(),
         )
         .foldl(|lhs, (op, rhs)| {
             Spanned(
@@ -155,14 +450,12 @@ fn binary_parser<'a>(
     let binary = binary
         .clone()
         .then(
-            just(Token::Whitespace)
-                .ignore_then(select! {
-                    Token::Plus => Binary::Add,
-                    Token::Minus => Binary::Sub,
-                })
-                .then_ignore(just(Token::Whitespace))
-                .then(binary)
-                .repeated(),
+            select! {
+                Token::Plus => Binary::Add,
+                Token::Minus => Binary::Sub,
+            }
+            .then(binary)
+            .repeated(),
         )
         .foldl(|lhs, (op, rhs)| {
             Spanned(
@@ -174,3 +467,64 @@ fn binary_parser<'a>(
 
     binary
 }
+
+fn fold_unary((op, rhs): (Spanned<Unary>, Spanned<Expr>)) -> Spanned<Expr> {
+    Spanned(
+        Expr::Unary(op.0, Box::new(rhs.clone())),
+        op.1.start..rhs.1.end,
+    )
+}
+
+#[derive(Clone)]
+enum Postfix {
+    Power(Spanned<Expr>),
+    Reverse,
+    Dual,
+}
+
+/// Powers `base^n`, reversions `base†` and, from LaTeX, duals `base^{*}` of the given base.
+/// Exponents are numbers, symbols or parenthesised expressions.
+fn power_parser<'a>(
+    base: impl Parser<Token, Spanned<Expr>, Error = Simple<Token>> + Clone + 'a,
+    expr: impl Parser<Token, Spanned<Expr>, Error = Simple<Token>> + Clone + 'a,
+) -> impl Parser<Token, Spanned<Expr>, Error = Simple<Token>> + Clone + 'a {
+    let exponent = select! {
+        Token::Number(number) => number_expr(number),
+        Token::Identifier(identifier) if identifier != ASSERT => Expr::Unknown(identifier),
+    }
+    .map_err(|error| {
+        expecting(
+            error,
+            [
+                Token::Number(String::new()),
+                Token::Identifier(String::new()),
+            ],
+        )
+    })
+    .map_with_span(Spanned)
+    .or(expr.delimited_by(just(Token::ParenOpen), just(Token::ParenClose)));
+
+    let exponent = just(Token::Minus)
+        .to(Unary::Neg)
+        .map_with_span(Spanned)
+        .repeated()
+        .then(exponent)
+        .foldr(|op, rhs| fold_unary((op, rhs)));
+
+    let power = just(Token::Hat).ignore_then(exponent).map(Postfix::Power);
+
+    let reverse = just(Token::Dagger).to(Postfix::Reverse);
+
+    let dual = just(Token::Dual).to(Postfix::Dual);
+
+    base.then(power.or(reverse).or(dual).map_with_span(Spanned).repeated())
+        .foldl(|lhs, rhs| {
+            let expr = match rhs.0 {
+                Postfix::Power(exponent) => Expr::Power(Box::new(lhs.clone()), Box::new(exponent)),
+                Postfix::Reverse => Expr::Unary(Unary::Reverse, Box::new(lhs.clone())),
+                Postfix::Dual => Expr::Unary(Unary::Dual, Box::new(lhs.clone())),
+            };
+            Spanned(expr, lhs.1.start..rhs.1.end)
+        })
+        .boxed()
+}
@@ -0,0 +1,129 @@
+use chumsky::error::{Simple, SimpleReason};
+use itertools::Itertools;
+
+use super::{
+    span::{Span, Spanned},
+    token::Token,
+    Syntax, ASSERT,
+};
+
+/// Syntax error located at a span of the input characters.
+#[derive(Debug, Clone)]
+pub struct SyntaxError {
+    pub span: Span,
+    pub message: String,
+    pub hint: Option<String>,
+}
+
+impl SyntaxError {
+    /// Error of the tokenizer, whose spans are already character spans.
+    pub fn from_char_error(error: Simple<char>) -> SyntaxError {
+        let message = match error.reason() {
+            SimpleReason::Custom(message) => message.clone(),
+            _ => match error.found() {
+                Some(c) => format!("Unexpected character '{c}'"),
+                None => "Unexpected end of input".to_string(),
+            },
+        };
+        SyntaxError {
+            span: error.span(),
+            message,
+            hint: None,
+        }
+    }
+
+    /// Error of the expression parser, whose spans are token spans.
+    /// Plain inputs are not expected to continue with tokens only LaTeX has.
+    pub fn from_token_error(
+        error: Simple<Token>,
+        tokens: &[Spanned<Token>],
+        input: &str,
+        syntax: Syntax,
+    ) -> SyntaxError {
+        let span = translate_span(error.span(), tokens, input);
+
+        let found = describe(error.found());
+        let expected = error
+            .expected()
+            .filter(|token| syntax == Syntax::Latex || !token.as_ref().is_some_and(Token::is_latex))
+            .map(|token| describe(token.as_ref()))
+            .sorted()
+            .dedup()
+            .collect_vec();
+
+        let message = match error.reason() {
+            SimpleReason::Unclosed { delimiter, .. } => {
+                format!("Unclosed {}", describe(Some(delimiter)))
+            }
+            SimpleReason::Custom(message) => message.clone(),
+            SimpleReason::Unexpected if expected.is_empty() => format!("Unexpected {found}"),
+            SimpleReason::Unexpected => format!(
+                "Unexpected {found}, expected {}",
+                expected.iter().join(", ")
+            ),
+        };
+
+        SyntaxError {
+            span,
+            message,
+            hint: hint(&error, tokens),
+        }
+    }
+}
+
+/// Translates a span of tokens into a span of characters.
+/// Errors at the end of input are located just past the last character.
+fn translate_span(span: Span, tokens: &[Spanned<Token>], input: &str) -> Span {
+    let end_of_input = input.chars().count();
+    let start = tokens
+        .get(span.start)
+        .map(|token| token.1.start)
+        .unwrap_or(end_of_input);
+    let end = if span.end > span.start && span.end <= tokens.len() {
+        tokens[span.end - 1].1.end
+    } else {
+        start + 1
+    };
+    start..end
+}
+
+fn describe(token: Option<&Token>) -> String {
+    match token {
+        None => "end of input".to_string(),
+        Some(
+            token @ (Token::Whitespace
+            | Token::Comment
+            | Token::Number(_)
+            | Token::Basis(_)
+            | Token::Identifier(_)),
+        ) => token.to_string(),
+        Some(token) => format!("`{token}`"),
+    }
+}
+
+/// Hints for common mistakes which lead to the given error.
+fn hint(error: &Simple<Token>, tokens: &[Spanned<Token>]) -> Option<String> {
+    let found = tokens.get(error.span().start).map(|token| &token.0);
+    let expected = |token: &Token| {
+        error
+            .expected()
+            .any(|expected| expected.as_ref() == Some(token))
+    };
+    let assertion = matches!(
+        tokens.first(),
+        Some(Spanned(Token::Identifier(keyword), _)) if keyword == ASSERT
+    );
+    match found {
+        Some(Token::Assign) => Some("Use `==` to compare two expressions".to_string()),
+        Some(Token::Equal | Token::NotEqual) => {
+            Some("Comparisons can neither be chained nor nested".to_string())
+        }
+        Some(found @ (Token::ParenClose | Token::BracketClose)) if !expected(found) => {
+            Some(format!("There is no opening delimiter matching `{found}`"))
+        }
+        _ if assertion => {
+            Some("Assertions compare two expressions, e.g. `assert a == b`".to_string())
+        }
+        _ => None,
+    }
+}
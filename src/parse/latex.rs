@@ -0,0 +1,319 @@
+use chumsky::prelude::*;
+
+use crate::algebra::latex::greek_letter;
+
+use super::{
+    span::{Span, Spanned},
+    token::Token,
+};
+
+type Tokens = Vec<Spanned<Token>>;
+
+/// Tokenizes LaTeX into the tokens of the same input written in galc's own notation,
+/// e.g. `\frac{a}{b}` into those of `((a) / (b))`,
+/// so that both are parsed by the same grammar into the same expressions.
+/// Tokens are located at the LaTeX they were read from.
+pub fn tokenize(input: &str) -> Result<Tokens, Vec<Simple<char>>> {
+    tokenizer().then_ignore(end()).parse(input)
+}
+
+fn tokenizer<'a>() -> impl Parser<char, Tokens, Error = Simple<char>> + Clone + 'a {
+    recursive(|tokens| {
+        // Braces group like parentheses.
+        let group: BoxedParser<char, Tokens, Simple<char>> = just('{')
+            .map_with_span(|_, span: Span| span)
+            .then(tokens)
+            .then(just('}').map_with_span(|_, span: Span| span))
+            .map(|((open, tokens), close)| parenthesized(open, tokens, close))
+            .boxed();
+
+        let digits = filter(|c: &char| c.is_ascii_digit())
+            .repeated()
+            .at_least(1)
+            .collect::<String>();
+        let subscript = subscript().boxed();
+
+        let number: BoxedParser<char, Tokens, Simple<char>> = digits
+            .chain::<char, _, _>(just('.').chain(digits).or_not().flatten())
+            .collect::<String>()
+            .map_with_span(|number, span| vec![Spanned(Token::Number(number), span)])
+            .boxed();
+
+        let basis: BoxedParser<char, Tokens, Simple<char>> = command("mathbf")
+            .ignore_then(just("{e}"))
+            .ignored()
+            .or(just('e').ignored())
+            .ignore_then(subscript.clone())
+            .map_with_span(|digits, span| {
+                let vectors = digits
+                    .chars()
+                    .map(|digit| digit.to_digit(10).unwrap() as usize)
+                    .collect();
+                vec![Spanned(Token::Basis(vectors), span)]
+            })
+            .boxed();
+
+        // Indexed Greek letters are vectors, e.g. `\gamma_{01}` is read as `γ0 γ1`.
+        let greek: BoxedParser<char, Tokens, Simple<char>> = name()
+            .try_map(|name, span| {
+                greek_letter(&name)
+                    .ok_or_else(|| Simple::custom(span, format!(r"\{name} is not a Greek letter")))
+            })
+            .then(subscript.clone().or_not())
+            .map_with_span(|(letter, subscript), span: Span| match subscript {
+                Some(indices) => indices
+                    .chars()
+                    .map(|index| {
+                        Spanned(Token::Identifier(format!("{letter}{index}")), span.clone())
+                    })
+                    .collect(),
+                None => vec![Spanned(Token::Identifier(letter.to_string()), span)],
+            })
+            .boxed();
+
+        // Every letter is a symbol of its own, and `I` is the pseudoscalar.
+        let letter: BoxedParser<char, Tokens, Simple<char>> =
+            filter(|c: &char| c.is_ascii_alphabetic())
+                .then(subscript.clone().or_not())
+                .map_with_span(|(letter, subscript), span| {
+                    let name = match (letter, subscript) {
+                        ('I', None) => "i".to_string(),
+                        (letter, subscript) => format!("{letter}{}", subscript.unwrap_or_default()),
+                    };
+                    vec![Spanned(Token::Identifier(name), span)]
+                })
+                .boxed();
+
+        let symbol: BoxedParser<char, Tokens, Simple<char>> = command("mathrm")
+            .or(command("mathit"))
+            .or(command("operatorname"))
+            .ignore_then(
+                filter(|c: &char| c.is_ascii_alphanumeric())
+                    .repeated()
+                    .at_least(1)
+                    .collect::<String>()
+                    .delimited_by(just('{'), just('}')),
+            )
+            .map_with_span(|name, span| vec![Spanned(Token::Identifier(name), span)])
+            .boxed();
+
+        let atom = choice((basis, greek, symbol, letter, number)).boxed();
+        let argument = group.clone().or(atom.clone()).boxed();
+
+        // Accents are parenthesized as a whole, since their operators are prefixes.
+        let accent: BoxedParser<char, Tokens, Simple<char>> = choice((
+            command("tilde").to(Token::Tilde),
+            command("widetilde").to(Token::Tilde),
+            command("hat").to(Token::Hat),
+            command("widehat").to(Token::Hat),
+            command("bar").to(Token::Excl),
+            command("overline").to(Token::Excl),
+        ))
+        .map_with_span(|token, span: Span| (token, span))
+        .then(argument.clone())
+        .map(|((token, span), argument)| {
+            let mut tokens = vec![Spanned(token, span.clone())];
+            tokens.extend(argument);
+            parenthesized(span.clone(), tokens, span)
+        })
+        .boxed();
+
+        // Fractions of integers are ratios like `1/2`, other fractions are divisions.
+        let fraction: BoxedParser<char, Tokens, Simple<char>> = command("frac")
+            .then(argument.clone())
+            .then(argument.clone())
+            .map_with_span(|((span, numerator), denominator), whole: Span| {
+                if let (Some(numerator), Some(denominator)) =
+                    (integer(&numerator), integer(&denominator))
+                {
+                    let ratio = format!("{numerator}/{denominator}");
+                    return vec![Spanned(Token::Number(ratio), whole)];
+                }
+                let mut tokens = numerator;
+                tokens.push(Spanned(Token::Solidus, span.clone()));
+                tokens.extend(denominator);
+                parenthesized(span.clone(), tokens, span)
+            })
+            .boxed();
+
+        // Superscripts are exponents, except for `^{*}` and `^{\dagger}`.
+        let dual = just('*').ignored().or(command("ast").ignored()).boxed();
+        let dagger = command("dagger").ignored().boxed();
+        let superscript: BoxedParser<char, Tokens, Simple<char>> = just('^')
+            .map_with_span(|_, span: Span| span)
+            .then(
+                choice((
+                    dual.clone()
+                        .or(dual.delimited_by(just('{'), just('}')))
+                        .to(vec![Token::Dual]),
+                    dagger
+                        .clone()
+                        .or(dagger.delimited_by(just('{'), just('}')))
+                        .to(vec![Token::Dagger]),
+                ))
+                .map(Ok)
+                .or(argument.map(Err)),
+            )
+            .map(|(span, exponent)| match exponent {
+                Ok(tokens) => tokens
+                    .into_iter()
+                    .map(|token| Spanned(token, span.clone()))
+                    .collect(),
+                Err(exponent) => {
+                    let mut tokens = vec![Spanned(Token::Hat, span)];
+                    tokens.extend(exponent);
+                    tokens
+                }
+            })
+            .boxed();
+
+        // Grade projections `\langle a \rangle_2`, where `\langle a \rangle` is the scalar part.
+        let angle_close: BoxedParser<char, Tokens, Simple<char>> = command("rangle")
+            .then(subscript.or_not())
+            .try_map(|(span, grade), _| {
+                let grade = match grade {
+                    Some(grade) => grade
+                        .parse()
+                        .map_err(|_| Simple::custom(span.clone(), "Grade out of range"))?,
+                    None => 0,
+                };
+                Ok(vec![Spanned(Token::AngleClose(grade), span)])
+            })
+            .boxed();
+
+        let simple_command: BoxedParser<char, Tokens, Simple<char>> = name()
+            .try_map(|name, span: Span| {
+                let token = match name.as_str() {
+                    "wedge" | "land" => Token::Wedge,
+                    "vee" | "lor" => Token::AntiWedge,
+                    "rfloor" => Token::LeftContraction,
+                    "lfloor" => Token::RightContraction,
+                    "cdot" => Token::InnerProduct,
+                    "ast" | "star" => Token::Asteriks,
+                    "bot" => Token::Bottom,
+                    "dagger" => Token::Dagger,
+                    "neq" | "ne" => Token::NotEqual,
+                    "lVert" => Token::BracketOpen,
+                    "rVert" => Token::BracketClose,
+                    "langle" => Token::AngleOpen,
+                    // Sizes of delimiters and spaces do not matter.
+                    "left" | "right" | "big" | "Big" | "bigg" | "Bigg" | "quad" | "qquad" => {
+                        return Ok(Vec::new())
+                    }
+                    "frac" | "tilde" | "widetilde" | "hat" | "widehat" | "bar" | "overline"
+                    | "mathbf" | "mathrm" | "mathit" | "operatorname" => {
+                        return Err(Simple::custom(
+                            span,
+                            format!(r"Missing argument of \{name}"),
+                        ))
+                    }
+                    _ => {
+                        return Err(Simple::custom(
+                            span,
+                            format!(r"Unknown LaTeX command \{name}"),
+                        ))
+                    }
+                };
+                Ok(vec![Spanned(token, span)])
+            })
+            .boxed();
+
+        let operator: BoxedParser<char, Tokens, Simple<char>> = choice((
+            just("==").to(Token::Equal),
+            just("=").to(Token::Assign),
+            just("+").to(Token::Plus),
+            just("-").to(Token::Minus),
+            just("*").to(Token::Asteriks),
+            just("/").to(Token::Solidus),
+            just("(").to(Token::ParenOpen),
+            just(")").to(Token::ParenClose),
+            just("[").to(Token::ParenOpen),
+            just("]").to(Token::ParenClose),
+            just(";").to(Token::Semicolon),
+        ))
+        .map_with_span(|token, span| vec![Spanned(token, span)])
+        .boxed();
+
+        // Spaces, alignment and line breaks of LaTeX documents.
+        let space: BoxedParser<char, Tokens, Simple<char>> = choice((
+            filter(|c: &char| c.is_whitespace()).ignored(),
+            just('\\').then(one_of(",;:! \\")).ignored(),
+            just('&').ignored(),
+            just('%')
+                .then(filter(|&c: &char| c != '\n').repeated())
+                .ignored(),
+        ))
+        .to(Vec::new())
+        .boxed();
+
+        // Unknown commands are reported by the last alternative,
+        // since the last of several errors at the same position is the one reported.
+        choice((
+            space,
+            accent,
+            fraction,
+            angle_close,
+            superscript,
+            atom,
+            group,
+            operator,
+            simple_command,
+        ))
+        .repeated()
+        .flatten()
+    })
+}
+
+/// Name of a command, without its backslash.
+fn name() -> impl Parser<char, String, Error = Simple<char>> + Clone {
+    just('\\').ignore_then(
+        filter(|c: &char| c.is_ascii_alphabetic())
+            .repeated()
+            .at_least(1)
+            .collect::<String>(),
+    )
+}
+
+/// The given command, such as `\frac`.
+fn command(expected: &'static str) -> impl Parser<char, Span, Error = Simple<char>> + Clone {
+    name().try_map(move |name, span: Span| {
+        if name == expected {
+            Ok(span)
+        } else {
+            Err(Simple::custom(span, format!(r"Expected \{expected}")))
+        }
+    })
+}
+
+/// Subscript of a single digit or of digits in braces, e.g. `_1` or `_{12}`.
+fn subscript() -> impl Parser<char, String, Error = Simple<char>> + Clone {
+    let digits = filter(|c: &char| c.is_ascii_digit())
+        .repeated()
+        .at_least(1)
+        .collect::<String>();
+    just('_').ignore_then(
+        digits
+            .delimited_by(just('{'), just('}'))
+            .or(filter(|c: &char| c.is_ascii_digit()).map(String::from)),
+    )
+}
+
+/// The integer of a number, possibly in braces.
+fn integer(tokens: &[Spanned<Token>]) -> Option<&str> {
+    match tokens {
+        [Spanned(Token::Number(number), _)]
+        | [Spanned(Token::ParenOpen, _), Spanned(Token::Number(number), _), Spanned(Token::ParenClose, _)]
+            if number.chars().all(|c| c.is_ascii_digit()) =>
+        {
+            Some(number)
+        }
+        _ => None,
+    }
+}
+
+fn parenthesized(open: Span, tokens: Tokens, close: Span) -> Tokens {
+    let mut parenthesized = vec![Spanned(Token::ParenOpen, open)];
+    parenthesized.extend(tokens);
+    parenthesized.push(Spanned(Token::ParenClose, close));
+    parenthesized
+}
@@ -1,6 +1,6 @@
 use std::ops::Range;
 
-use crate::parse::{Expr, Token};
+use crate::parse::{Expr, Statement, Token};
 
 pub type Span = Range<usize>;
 
@@ -18,11 +18,32 @@ pub fn translate_spans(expr: Spanned<Expr>, tokens: &Vec<Spanned<Token>>) -> Spa
             Box::new(translate_spans(*lhs, tokens)),
             Box::new(translate_spans(*rhs, tokens)),
         ),
-        Expr::Power(expr, exp) => Expr::Power(Box::new(translate_spans(*expr, tokens)), exp),
+        Expr::Power(expr, exp) => Expr::Power(
+            Box::new(translate_spans(*expr, tokens)),
+            Box::new(translate_spans(*exp, tokens)),
+        ),
         Expr::Unary(op, expr) => Expr::Unary(op, Box::new(translate_spans(*expr, tokens))),
         Expr::Norm(expr) => Expr::Norm(Box::new(translate_spans(*expr, tokens))),
+        Expr::Grade(expr, grade) => Expr::Grade(Box::new(translate_spans(*expr, tokens)), grade),
         expr => expr,
     };
 
     Spanned(expr, span)
 }
+
+pub fn translate_statement_spans(statement: Statement, tokens: &Vec<Spanned<Token>>) -> Statement {
+    match statement {
+        Statement::Expr(expr) => Statement::Expr(translate_spans(expr, tokens)),
+        Statement::Compare(comparison, lhs, rhs) => Statement::Compare(
+            comparison,
+            translate_spans(lhs, tokens),
+            translate_spans(rhs, tokens),
+        ),
+        Statement::Assert(comparison, lhs, rhs) => Statement::Assert(
+            comparison,
+            translate_spans(lhs, tokens),
+            translate_spans(rhs, tokens),
+        ),
+        Statement::Assign(name, expr) => Statement::Assign(name, translate_spans(expr, tokens)),
+    }
+}
@@ -0,0 +1,381 @@
+use crate::{
+    algebra::{latex::Latex, notation::Notation},
+    interpret::expr::{Expr, Statement},
+};
+
+use super::{parse, parse_statements, spacing_hints, span::Spanned, Syntax};
+
+/// Renders the parsed expression tree as an s-expression.
+fn tree(input: &str) -> String {
+    match parse(input).expect("Syntax error") {
+        Statement::Expr(expr) => sexpr(&expr),
+        Statement::Compare(comparison, lhs, rhs) | Statement::Assert(comparison, lhs, rhs) => {
+            format!("({comparison:?} {} {})", sexpr(&lhs), sexpr(&rhs))
+        }
+        Statement::Assign(name, expr) => format!("(Assign {name} {})", sexpr(&expr)),
+    }
+}
+
+fn sexpr(expr: &Spanned<Expr>) -> String {
+    match &expr.0 {
+        Expr::Number(n) => n.to_string(),
+        Expr::Pseudoscalar => "i".to_string(),
+        Expr::Basis(vectors) => format!(
+            "e{}",
+            vectors.iter().map(|v| v.to_string()).collect::<String>()
+        ),
+        Expr::Unknown(name) => name.clone(),
+        Expr::InvalidNumber(number) => number.clone(),
+        Expr::Bottom => "_|_".to_string(),
+        Expr::Binary(op, lhs, rhs) => format!("({op:?} {} {})", sexpr(lhs), sexpr(rhs)),
+        Expr::Power(base, exponent) => format!("(Power {} {})", sexpr(base), sexpr(exponent)),
+        Expr::Unary(op, x) => format!("({op:?} {})", sexpr(x)),
+        Expr::Norm(x) => format!("(Norm {})", sexpr(x)),
+        Expr::Grade(x, grade) => format!("(Grade {} {grade})", sexpr(x)),
+    }
+}
+
+#[test]
+fn binary_operators() {
+    for (operator, name) in [
+        (r"/\", "Exterior"),
+        (r"\/", "Regressive"),
+        ("-|", "LeftContraction"),
+        ("|-", "RightContraction"),
+        ("|", "Inner"),
+        ("*", "Scalar"),
+        ("/", "Divide"),
+        ("+", "Add"),
+        ("-", "Sub"),
+    ] {
+        let expected = format!("({name} a b)");
+        assert_eq!(tree(&format!("a {operator} b")), expected);
+        assert_eq!(tree(&format!("a{operator}b")), expected);
+        assert_eq!(tree(&format!("a {operator}b")), expected);
+        assert_eq!(tree(&format!("a{operator} b")), expected);
+    }
+    assert_eq!(tree("a b"), "(Geometric a b)");
+    assert_eq!(tree("a(b)"), "(Geometric a b)");
+    assert_eq!(tree("2a"), "(Geometric 2 a)");
+}
+
+#[test]
+fn unary_operators() {
+    for (operator, name) in [
+        ("-", "Neg"),
+        ("*", "Dual"),
+        ("~", "Reverse"),
+        ("!", "Conjugate"),
+        ("^", "Involution"),
+    ] {
+        assert_eq!(tree(&format!("{operator}a")), format!("({name} a)"));
+        assert_eq!(tree(&format!("{operator} a")), format!("({name} a)"));
+        assert_eq!(tree(&format!("-{operator}a")), format!("(Neg ({name} a))"));
+    }
+}
+
+#[test]
+fn left_associativity() {
+    for (operator, name) in [
+        (r"/\", "Exterior"),
+        (r"\/", "Regressive"),
+        ("-|", "LeftContraction"),
+        ("|-", "RightContraction"),
+        ("|", "Inner"),
+        ("*", "Scalar"),
+        ("/", "Divide"),
+        ("+", "Add"),
+        ("-", "Sub"),
+    ] {
+        assert_eq!(
+            tree(&format!("a {operator} b {operator} c")),
+            format!("({name} ({name} a b) c)")
+        );
+    }
+    assert_eq!(tree("a b c"), "(Geometric (Geometric a b) c)");
+    assert_eq!(tree("a^2^3"), "(Power (Power a 2) 3)");
+    assert_eq!(tree(r"a /\ b | c"), "(Inner (Exterior a b) c)");
+    assert_eq!(tree("a + b - c"), "(Sub (Add a b) c)");
+}
+
+#[test]
+fn precedence() {
+    assert_eq!(
+        tree(r"a + b /\ c d"),
+        "(Add a (Exterior b (Geometric c d)))"
+    );
+    assert_eq!(tree(r"a b /\ c"), "(Exterior (Geometric a b) c)");
+    assert_eq!(tree("a / b c"), "(Divide a (Geometric b c))");
+    assert_eq!(tree("a * b - c"), "(Sub (Scalar a b) c)");
+    assert_eq!(tree("a b^2"), "(Geometric a (Power b 2))");
+    assert_eq!(tree("a^-1"), "(Power a (Neg 1))");
+    assert_eq!(tree("-a^2"), "(Power (Neg a) 2)");
+    assert_eq!(tree("~a b"), "(Geometric (Reverse a) b)");
+    assert_eq!(tree("[a + b] c"), "(Geometric (Norm (Add a b)) c)");
+    assert_eq!(tree("(a + b) c"), "(Geometric (Add a b) c)");
+    assert_eq!(tree("a + b == b + a"), "(Equal (Add a b) (Add b a))");
+    assert_eq!(tree("assert a != b"), "(NotEqual a b)");
+}
+
+#[test]
+fn juxtaposition_with_prefix_operators() {
+    // `-` and `*` following an operand are infix operators.
+    assert_eq!(tree("a -b"), "(Sub a b)");
+    assert_eq!(tree("a *b"), "(Scalar a b)");
+    assert_eq!(tree("a (-b)"), "(Geometric a (Neg b))");
+    assert_eq!(tree("a - -b"), "(Sub a (Neg b))");
+    assert_eq!(tree("a ^b"), "(Power a b)");
+    assert_eq!(tree("a (^b)"), "(Geometric a (Involution b))");
+    // The other prefix operators cannot be mistaken for infix operators.
+    assert_eq!(tree("a ~b"), "(Geometric a (Reverse b))");
+    assert_eq!(tree("a !b"), "(Geometric a (Conjugate b))");
+    assert_eq!(tree("a ~-b"), "(Geometric a (Reverse (Neg b)))");
+    // `|-` is a right contraction regardless of spacing.
+    assert_eq!(tree("a|-b"), "(RightContraction a b)");
+    assert_eq!(tree("a | -b"), "(Inner a (Neg b))");
+}
+
+#[test]
+fn misleading_spacing() {
+    let hinted = |input: &str| {
+        spacing_hints(input)
+            .into_iter()
+            .map(|Spanned(_, span)| span.start)
+            .collect::<Vec<_>>()
+    };
+    // Spacing which suggests a prefix operator rather than the infix one parsed.
+    assert_eq!(hinted("a -b"), [2]);
+    assert_eq!(hinted("a *b"), [2]);
+    assert_eq!(hinted("a ^b"), [2]);
+    assert_eq!(hinted("a|-b"), [1]);
+    assert_eq!(hinted("a |-(b + c)"), [2]);
+    assert_eq!(hinted("(a) -[b] + c ^2"), [4, 13]);
+    // Spacing which agrees with the reading.
+    for input in [
+        "a - b", "a-b", "a*b", "a^b", "a (-b)", "a - -b", "-a", "a |- b", "a ⌊b",
+    ] {
+        assert!(hinted(input).is_empty(), "{input}");
+    }
+}
+
+#[test]
+fn unicode_aliases() {
+    for (unicode, ascii) in [
+        ("a ∧ b", r"a /\ b"),
+        ("a ∨ b", r"a \/ b"),
+        ("a ⌋ b", "a -| b"),
+        ("a ⌊ b", "a |- b"),
+        ("a·b", "a | b"),
+        ("⋆a", "*a"),
+        ("a ⋆ b", "a * b"),
+        ("𝐼", "i"),
+        ("a²", "a^2"),
+        ("a⁻¹", "a^-1"),
+        ("a¹⁰ b", "a^10 b"),
+        ("(a b)²⁻¹", "(a b)^2^-1"),
+    ] {
+        assert_eq!(tree(unicode), tree(ascii));
+    }
+    assert_eq!(tree("a†"), "(Reverse a)");
+    assert_eq!(tree("a b†²"), "(Geometric a (Power (Reverse b) 2))");
+}
+
+#[test]
+fn decimal_literals() {
+    for (literal, value) in [
+        ("0.25", "1/4"),
+        ("0.1", "1/10"),
+        (".5", "1/2"),
+        ("1.5e-3", "3/2000"),
+        ("1e2", "100"),
+        ("2.5E+1", "25"),
+        ("6/4", "3/2"),
+    ] {
+        assert_eq!(tree(literal), value);
+    }
+    assert_eq!(tree("1e2 e2"), "(Geometric 100 e2)");
+    assert_eq!(tree("2 e1"), "(Geometric 2 e1)");
+    assert_eq!(tree("1/0"), "1/0");
+    assert_eq!(tree("1e100000"), "1e100000");
+    // A second decimal point does not start another literal.
+    for input in ["1.5.5", ".5.5", "1e2.5", "1/2.5"] {
+        assert!(parse(input).is_err(), "{input}");
+    }
+    assert_eq!(tree("1.5 .5"), "(Geometric 3/2 1/2)");
+}
+
+#[test]
+fn exponents() {
+    assert_eq!(tree("a^n"), "(Power a n)");
+    assert_eq!(tree("a^-n"), "(Power a (Neg n))");
+    assert_eq!(tree("a^(n + 1)"), "(Power a (Add n 1))");
+    assert_eq!(tree("a^(2)^3"), "(Power (Power a 2) 3)");
+    assert_eq!(
+        tree("a^99999999999999999999"),
+        "(Power a 99999999999999999999)"
+    );
+    assert!(parse("a^e1").is_err());
+}
+
+#[test]
+fn statements() {
+    let count = |input: &str| {
+        parse_statements(input, &Notation::default(), Syntax::Plain)
+            .expect("Syntax error")
+            .len()
+    };
+    assert_eq!(count("a; b == c; assert a != b"), 3);
+    assert_eq!(count("a;; b;"), 2);
+    assert_eq!(count("# only a comment"), 0);
+    assert_eq!(count("a # (; b"), 1);
+    assert_eq!(tree("a + b # c"), "(Add a b)");
+    assert_eq!(tree("a +\n b"), "(Add a b)");
+    assert!(parse("a; b").is_err());
+
+    // Spans point into the whole input, not into the single statement.
+    let errors = parse_statements("a; (b", &Notation::default(), Syntax::Plain)
+        .err()
+        .unwrap();
+    assert_eq!(errors[0].span, 5..6);
+    let errors = parse_statements("a; b +; c", &Notation::default(), Syntax::Plain)
+        .err()
+        .unwrap();
+    assert_eq!(errors[0].span, 6..7);
+}
+
+#[test]
+fn assertions() {
+    assert_eq!(tree("assert a == b"), "(Equal a b)");
+    assert_eq!(tree("assert a b != c"), "(NotEqual (Geometric a b) c)");
+    // `assert` is a keyword, so a missing comparison is not read as a product with it.
+    for input in ["assert a", "assert e0 e0", "assert", "a assert", "a^assert"] {
+        assert!(parse(input).is_err(), "{input}");
+    }
+    let errors = parse("assert a").err().unwrap();
+    assert_eq!(errors[0].span, 8..9);
+    assert!(errors[0].hint.is_some());
+}
+
+#[test]
+fn assignments() {
+    assert_eq!(tree("a = b + c"), "(Assign a (Add b c))");
+    assert_eq!(tree("R = e12^n"), "(Assign R (Power e12 n))");
+    assert_eq!(tree("a = $12 + ans"), "(Assign a (Add $12 ans))");
+    for input in [
+        "i = 2",
+        "e1 = 2",
+        "ans = 2",
+        "$1 = 2",
+        "$ = 2",
+        "a b = c",
+        "a = b = c",
+        "a = b == c",
+    ] {
+        assert!(parse(input).is_err(), "{input}");
+    }
+    let errors = parse("a + b = c").err().unwrap();
+    assert_eq!(errors[0].span, 6..7);
+    assert!(errors[0].hint.is_some());
+}
+
+#[test]
+fn named_bases() {
+    let trees = |input: &str, notation: &Notation| {
+        let statements = parse_statements(input, notation, Syntax::Plain).expect("Syntax error");
+        match &statements[..] {
+            [Statement::Expr(expr)] => sexpr(expr),
+            _ => panic!("Not a single expression"),
+        }
+    };
+
+    let quaternions = Notation::new(
+        vec!["i".to_string(), "j".to_string()],
+        vec![(vec![0, 1], "k".to_string())],
+    );
+    assert_eq!(
+        trees("i j k", &quaternions),
+        "(Geometric (Geometric e0 e1) e01)"
+    );
+    assert!(parse_statements("e0", &quaternions, Syntax::Plain).is_err());
+
+    let pga = Notation::pga(2);
+    assert_eq!(trees("e0 + e12", &pga), "(Add e2 e01)");
+    let errors = parse_statements("e3", &pga, Syntax::Plain).err().unwrap();
+    assert_eq!(errors[0].span, 0..2);
+}
+
+#[test]
+fn latex() {
+    let latex_tree = |input: &str| {
+        let statements =
+            parse_statements(input, &Notation::default(), Syntax::Latex).expect("Syntax error");
+        match &statements[..] {
+            [Statement::Expr(expr)] => sexpr(expr),
+            _ => panic!("Not a single expression"),
+        }
+    };
+
+    assert_eq!(latex_tree(r"\mathbf{e}_{12} \wedge a"), "(Exterior e12 a)");
+    assert_eq!(latex_tree(r"\tilde{R}"), "(Reverse R)");
+    assert_eq!(latex_tree(r"R^{-1}"), "(Power R (Neg 1))");
+    assert_eq!(latex_tree(r"\frac{1}{2}"), "1/2");
+    assert_eq!(latex_tree(r"\frac{a}{2}"), "(Divide a 2)");
+    assert_eq!(latex_tree(r"\langle A \rangle_2"), "(Grade A 2)");
+    assert_eq!(
+        latex_tree(r"\langle a b \rangle + b"),
+        "(Add (Grade (Geometric a b) 0) b)"
+    );
+    assert_eq!(latex_tree(r"a^{*} \wedge b"), "(Exterior (Dual a) b)");
+    assert_eq!(
+        latex_tree(r"\left\lVert \alpha_{1} e_1 \cdot \mathrm{ab} \right\rVert"),
+        "(Norm (Inner (Geometric α1 e1) ab))"
+    );
+    assert_eq!(
+        latex_tree(r"X^{*} + X^\dagger"),
+        "(Add (Dual X) (Reverse X))"
+    );
+    assert_eq!(latex_tree(r"\gamma_{01}"), "(Geometric γ0 γ1)");
+    assert_eq!(latex_tree(r"a^{2}\, I"), "(Geometric (Power a 2) i)");
+
+    // Rendered expressions are read as the same expressions again.
+    let notation = Notation::default();
+    let latex = Latex::new(&notation, None);
+    for input in [
+        "1/2 a^2 e12",
+        "~(a + b) /\\ c^-1 - !^d",
+        "*(e1 -| e12) \\/ [a] |- b * c",
+        "(a - b) (c + d)^(n + 1)",
+    ] {
+        let Statement::Expr(expr) = parse(input).expect("Syntax error") else {
+            panic!("Not an expression");
+        };
+        assert_eq!(latex_tree(&expr.0.latex(&latex)), sexpr(&expr), "{input}");
+    }
+    for input in [
+        r"\langle b \rangle_1 * c^{*}",
+        r"{a^{2}}^{*} + \langle a \rangle",
+    ] {
+        let statements = parse_statements(input, &notation, Syntax::Latex).expect("Syntax error");
+        let [Statement::Expr(expr)] = &statements[..] else {
+            panic!("Not an expression");
+        };
+        assert_eq!(latex_tree(&expr.0.latex(&latex)), sexpr(expr), "{input}");
+    }
+
+    // Grade projections and postfix duals have no plain notation.
+    for input in ["⟨a⟩_2", "a^*", "a^* /\\ b"] {
+        assert!(parse(input).is_err(), "{input}");
+    }
+    let message = |input: &str, syntax| {
+        parse_statements(input, &notation, syntax).err().unwrap()[0]
+            .message
+            .clone()
+    };
+    assert!(!message("(a", Syntax::Plain).contains(r"\langle"));
+    assert!(message("(a", Syntax::Latex).contains(r"\langle"));
+
+    assert!(parse_statements(r"\foo", &notation, Syntax::Latex).is_err());
+    assert!(parse_statements(r"\frac{1}{", &notation, Syntax::Latex).is_err());
+    assert_eq!(Syntax::Plain.of(r" \tilde{R}"), Syntax::Latex);
+    assert_eq!(Syntax::Plain.of("~R"), Syntax::Plain);
+}
@@ -5,6 +5,7 @@ use super::span::Spanned;
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Token {
     Whitespace,
+    Comment,
     Number(String),
     Basis(Vec<usize>),
     Identifier(String),
@@ -13,6 +14,10 @@ pub enum Token {
     ParenClose,
     BracketOpen,
     BracketClose,
+    /// Opening angle bracket of a LaTeX grade projection, `\langle`.
+    AngleOpen,
+    /// Closing angle bracket along with the grade of its subscript, which is zero without one.
+    AngleClose(usize),
     Plus,
     Minus,
     Tilde,
@@ -25,6 +30,20 @@ pub enum Token {
     Asteriks,
     Solidus,
     Hat,
+    Assign,
+    Equal,
+    NotEqual,
+    Dagger,
+    /// Postfix dual of LaTeX, `^{*}`.
+    Dual,
+    Semicolon,
+}
+
+impl Token {
+    /// Whether only LaTeX inputs have this token.
+    pub fn is_latex(&self) -> bool {
+        matches!(self, Token::AngleOpen | Token::AngleClose(_) | Token::Dual)
+    }
 }
 
 pub fn tokenize(input: &str) -> Result<Vec<Spanned<Token>>, Vec<Simple<char>>> {
@@ -40,36 +59,108 @@ fn tokenizer<'a>() -> impl Parser<char, Vec<Spanned<Token>>, Error = Simple<char
             .map_with_span(Spanned)
             .boxed();
 
+    let comment: BoxedParser<char, Spanned<Token>, Simple<char>> = just('#')
+        .then(filter(|&c: &char| c != '\n').repeated())
+        .to(Token::Comment)
+        .map_with_span(Spanned)
+        .boxed();
+
     let operator: BoxedParser<char, Spanned<Token>, Simple<char>> = choice((
         just(r"+").to(Token::Plus),
+        just(r"-|").to(Token::LeftContraction),
         just(r"-").to(Token::Minus),
         just(r"~").to(Token::Tilde),
         just(r"/\").to(Token::Wedge),
         just(r"\/").to(Token::AntiWedge),
-        just(r"-|").to(Token::LeftContraction),
         just(r"|-").to(Token::RightContraction),
         just(r"|").to(Token::InnerProduct),
         just(r"*").to(Token::Asteriks),
         just(r"/").to(Token::Solidus),
         just(r"^").to(Token::Hat),
+        just(r"==").to(Token::Equal),
+        just(r"!=").to(Token::NotEqual),
+        just(r"=").to(Token::Assign),
         just(r"!").to(Token::Excl),
     ))
     .map_with_span(Spanned)
     .boxed();
 
+    // Notation of LaTeX-rendered documents.
+    let unicode_operator: BoxedParser<char, Spanned<Token>, Simple<char>> = select! {
+        '∧' => Token::Wedge,
+        '∨' => Token::AntiWedge,
+        '⌋' => Token::LeftContraction,
+        '⌊' => Token::RightContraction,
+        '·' => Token::InnerProduct,
+        '⋆' => Token::Asteriks,
+        '†' => Token::Dagger,
+        '𝐼' => Token::Identifier("i".to_string()),
+    }
+    .map_with_span(Spanned)
+    .boxed();
+
+    // Superscript exponents such as `²` or `⁻¹` expand to `^2` and `^-1`.
+    let superscript: BoxedParser<char, Vec<Spanned<Token>>, Simple<char>> = just('⁻')
+        .or_not()
+        .then(
+            filter_map(
+                |span, c: char| match "⁰¹²³⁴⁵⁶⁷⁸⁹".chars().position(|s| s == c) {
+                    Some(digit) => Ok(char::from_digit(digit as u32, 10).unwrap()),
+                    None => Err(Simple::custom(
+                        span,
+                        format!("'{c}' is not a superscript digit"),
+                    )),
+                },
+            )
+            .repeated()
+            .at_least(1)
+            .collect::<String>(),
+        )
+        .map_with_span(|(minus, digits), span: std::ops::Range<usize>| {
+            let mut tokens = vec![Spanned(Token::Hat, span.clone())];
+            if minus.is_some() {
+                tokens.push(Spanned(Token::Minus, span.clone()));
+            }
+            tokens.push(Spanned(Token::Number(digits), span));
+            tokens
+        })
+        .boxed();
+
     let bottom: BoxedParser<char, Spanned<Token>, Simple<char>> = just(r"_|_")
         .to(Token::Bottom)
         .map_with_span(Spanned)
         .boxed();
 
-    let number: BoxedParser<char, Spanned<Token>, Simple<char>> = text::int(10)
-        .then(just('/').ignore_then(text::int(10)).or_not())
-        .map(|(denom, numerator)| {
-            Token::Number(match numerator {
-                Some(numerator) => format!("{denom}/{numerator}"),
-                None => denom,
-            })
+    let ratio = text::int(10)
+        .then_ignore(just('/'))
+        .then(text::int(10))
+        .map(|(numerator, denominator)| format!("{numerator}/{denominator}"));
+
+    // Scientific notation requires a digit before the `e`, so that `e2` remains a basis.
+    let digits = filter(|c: &char| c.is_ascii_digit()).repeated().at_least(1);
+    let fraction = just('.').chain(digits);
+    let mantissa = digits
+        .chain::<char, _, _>(fraction.or_not().flatten())
+        .or(fraction);
+    let exponent = one_of("eE")
+        .chain(one_of("+-").or_not())
+        .chain::<char, _, _>(digits);
+    let decimal = mantissa
+        .chain::<char, _, _>(exponent.or_not().flatten())
+        .collect::<String>();
+
+    // Another decimal point right after a number is malformed, e.g. `1.5.5` is not `1.5 .5`.
+    let number: BoxedParser<char, Spanned<Token>, Simple<char>> = ratio
+        .or(decimal)
+        .then(fraction.or_not())
+        .try_map(|(number, fraction), span| match fraction {
+            None => Ok(number),
+            Some(_) => Err(Simple::custom(
+                span,
+                "Numbers have at most one decimal point",
+            )),
         })
+        .map(Token::Number)
         .map_with_span(Spanned)
         .boxed();
 
@@ -88,8 +179,8 @@ fn tokenizer<'a>() -> impl Parser<char, Vec<Spanned<Token>>, Error = Simple<char
 
     let identifier: BoxedParser<char, Spanned<Token>, Simple<char>> =
         filter(|&c| unicode_ident::is_xid_start(c))
-            .map(|c| String::from(c))
-            .then(filter(|&c| unicode_ident::is_xid_continue(c)).repeated())
+            .map(String::from)
+            .then(filter(|&c| unicode_ident::is_xid_continue(c) && c != '·').repeated())
             .foldl(|mut s, c| {
                 s.push(c);
                 s
@@ -98,19 +189,40 @@ fn tokenizer<'a>() -> impl Parser<char, Vec<Spanned<Token>>, Error = Simple<char
             .map_with_span(Spanned)
             .boxed();
 
+    // Previous results are numbered `$1`, `$2`, ...
+    let result: BoxedParser<char, Spanned<Token>, Simple<char>> = just('$')
+        .chain::<char, _, _>(filter(|c: &char| c.is_ascii_digit()).repeated().at_least(1))
+        .collect::<String>()
+        .map(Token::Identifier)
+        .map_with_span(Spanned)
+        .boxed();
+
     let delimiter: BoxedParser<char, Spanned<Token>, Simple<char>> = select! {
         '(' => Token::ParenOpen,
         ')' => Token::ParenClose,
         '[' => Token::BracketOpen,
         ']' => Token::BracketClose,
+        ';' => Token::Semicolon,
     }
     .map_with_span(Spanned)
     .boxed();
 
     choice((
-        whitespace, bottom, operator, number, basis, identifier, delimiter,
+        whitespace,
+        comment,
+        bottom,
+        operator,
+        unicode_operator,
+        number,
+        basis,
+        identifier,
+        result,
+        delimiter,
     ))
+    .map(|token| vec![token])
+    .or(superscript)
     .repeated()
+    .flatten()
     .then_ignore(end())
 }
 
@@ -118,6 +230,7 @@ impl std::fmt::Display for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Token::Whitespace => write!(f, "whitespace"),
+            Token::Comment => write!(f, "comment"),
             Token::Number(_) => write!(f, "number"),
             Token::Basis(_) => write!(f, "basis"),
             Token::Identifier(_) => write!(f, "identifier"),
@@ -126,6 +239,8 @@ impl std::fmt::Display for Token {
             Token::ParenClose => write!(f, ")"),
             Token::BracketOpen => write!(f, "["),
             Token::BracketClose => write!(f, "]"),
+            Token::AngleOpen => write!(f, "\\langle"),
+            Token::AngleClose(_) => write!(f, "\\rangle"),
             Token::Plus => write!(f, "+"),
             Token::Minus => write!(f, "-"),
             Token::Tilde => write!(f, "~"),
@@ -138,6 +253,12 @@ impl std::fmt::Display for Token {
             Token::Asteriks => write!(f, "*"),
             Token::Solidus => write!(f, "/"),
             Token::Hat => write!(f, "^"),
+            Token::Assign => write!(f, "="),
+            Token::Equal => write!(f, "=="),
+            Token::NotEqual => write!(f, "!="),
+            Token::Dagger => write!(f, "†"),
+            Token::Dual => write!(f, "^{{*}}"),
+            Token::Semicolon => write!(f, ";"),
         }
     }
 }
@@ -1,16 +1,56 @@
-use std::io::stdin;
+mod config;
+mod editor;
+mod input;
+mod script;
+
+#[cfg(test)]
+mod test;
+
+use std::{
+    fs,
+    io::{stdin, IsTerminal},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
 
 use structopt::StructOpt;
 
-use crate::{algebra::metric, interpret::eval, parse};
+use itertools::Itertools;
+
+use crate::{
+    algebra::{
+        basis::Basis, latex::Latex, metric, notation::Notation, polynom::Polynomial, preset,
+        style::Style,
+    },
+    interpret::{eval, expr::Statement},
+    parse::{self, error::SyntaxError, span::Spanned, Syntax},
+};
+
+use self::{
+    config::{algebra_name, Config, Format},
+    editor::LineEditor,
+    input::Input,
+};
 
 #[derive(StructOpt, Debug)]
 #[structopt()]
 struct Options {
-    /// Expression to evaluate. Otherwise enter interactive mode
+    /// Expression to evaluate, or script file ending in .ga to execute. Otherwise enter interactive mode
     #[structopt()]
     expression: Option<String>,
 
+    /// Script file to execute, one statement or command per line.
+    #[structopt(short, long, parse(from_os_str))]
+    file: Option<PathBuf>,
+
+    /// Session saved by :save to continue with.
+    #[structopt(long, parse(from_os_str))]
+    session: Option<PathBuf>,
+
+    /// Only check the syntax of the expression, script or piped input, without evaluating it.
+    #[structopt(long)]
+    check: bool,
+
     /// Number of dimensions with metric 1.
     #[structopt(short, long)]
     positive: Option<usize>,
@@ -38,137 +78,996 @@ struct Options {
     /// Plane-based geometric algebra.
     #[structopt(long)]
     pga: Option<usize>,
+
+    /// Named algebra, such as sta, cga3 or "pga 3". Use :algebras to list all of them.
+    #[structopt(long)]
+    algebra: Option<String>,
+
+    /// Print monomials as they are, without collecting them by basis.
+    #[structopt(long)]
+    raw: bool,
+
+    /// Format of results: collected, raw, latex or "latex align".
+    #[structopt(long)]
+    format: Option<Format>,
+
+    /// LaTeX macro of all vectors, such as \gamma. Otherwise derived from their names.
+    #[structopt(long)]
+    latex_basis: Option<String>,
+
+    /// Syntax of typed and piped inputs: plain or latex. Scripts and saved sessions are plain,
+    /// while inputs starting with a backslash are LaTeX anyway.
+    #[structopt(long)]
+    input: Option<Syntax>,
 }
 
-pub fn repl() {
+pub fn repl() -> ExitCode {
     let options = Options::from_args();
 
-    let mut metric = metric::Metric(vec![]);
-    if options.hyperbolic {
-        metric.0 = vec![metric::Square::Pos];
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(message) => {
+            eprintln!("{message}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Options override the configured algebra.
+    let algebra = if options.hyperbolic {
+        "hyperbolic".to_string()
     } else if options.complex {
-        metric.0 = vec![metric::Square::Neg];
+        "complex".to_string()
     } else if options.dual {
-        metric.0 = vec![metric::Square::Zero];
+        "dual".to_string()
+    } else if let Some(name) = options.algebra {
+        name
     } else if let Some(n) = options.pga {
-        metric.0 = vec![metric::Square::Pos; n];
-        metric.0.push(metric::Square::Zero);
+        format!("pga {n}")
+    } else if options.positive.is_some() || options.negative.is_some() || options.zero.is_some() {
+        format!(
+            "{},{},{}",
+            options.positive.unwrap_or(0),
+            options.negative.unwrap_or(0),
+            options.zero.unwrap_or(0)
+        )
     } else {
-        if let Some(p) = options.positive {
-            metric
-                .0
-                .extend(std::iter::repeat(metric::Square::Pos).take(p));
+        config.algebra.clone().unwrap_or_else(|| "0".to_string())
+    };
+
+    let algebra = match parse_metric(&algebra) {
+        Ok(algebra) => algebra,
+        Err(message) => {
+            eprintln!("{message}");
+            return ExitCode::FAILURE;
         }
-        if let Some(q) = options.negative {
-            metric
-                .0
-                .extend(std::iter::repeat(metric::Square::Neg).take(q));
+    };
+
+    let preload = config.preload.clone();
+    let mut session = Session::new(config);
+    if options.raw {
+        session.format = Format::Raw;
+    } else if let Some(format) = options.format {
+        session.format = format;
+    }
+    if options.latex_basis.is_some() {
+        session.latex_basis = options.latex_basis;
+    }
+    if let Some(syntax) = options.input {
+        session.syntax = syntax;
+    }
+    if let Err(message) = session.set_algebra(algebra) {
+        eprintln!("{message}");
+    }
+
+    // Preloaded scripts are only checked along with the ones they serve.
+    let mut outcome = Outcome::default();
+    if !options.check {
+        for script in preload {
+            outcome = outcome.and(session.load(&script));
         }
-        if let Some(r) = options.zero {
-            metric
-                .0
-                .extend(std::iter::repeat(metric::Square::Zero).take(r));
+        if let Some(saved) = &options.session {
+            outcome = outcome.and(session.restore(saved, false));
         }
     }
 
-    if metric.0.len() > 9 {
-        println!("Only dimensions up to 9 are supported due to notational constraints");
-        return;
+    let (script, expression) = match (options.file, options.expression) {
+        (None, Some(expression)) if is_script(&expression) => {
+            (Some(PathBuf::from(expression)), None)
+        }
+        (script, expression) => (script, expression),
+    };
+
+    if script.is_some() || expression.is_some() {
+        if let Some(script) = script {
+            outcome = outcome.and(if options.check {
+                script::check(&script, &session.notation)
+            } else {
+                session.load(&script)
+            });
+        }
+        if let Some(expression) = expression {
+            let input = Input::new(&expression);
+            outcome = outcome.and(if options.check {
+                check(&input, &session.notation, session.syntax)
+            } else {
+                session.execute(&input, false)
+            });
+        }
+        return outcome.exit_code();
     }
 
-    if let Some(expression) = options.expression {
-        let expr = match parse::parse(&expression) {
-            Some(expr) => expr,
-            None => return,
-        };
-        match eval::eval(expr, &(&metric)) {
-            Ok(result) => println!("{}", result.optimize()),
-            Err(eval::Undefined(_)) => println!("_|_"),
-        };
-        return;
+    // Piped input is executed like a script.
+    if !stdin().is_terminal() {
+        return outcome
+            .and(script::run_stdin(&mut session, options.check))
+            .exit_code();
+    }
+
+    // Interactively, only failed assertions determine the exit code, since typos are corrected.
+    // Failures of preloaded scripts are pointed out, since they scroll by before the prompt.
+    let mut failed_assertion = outcome.failed_assertion;
+    if outcome.failed() {
+        eprintln!("Preloading failed, the session continues with what was loaded.\n");
     }
 
-    loop {
-        let mut input = String::new();
-        stdin()
-            .read_line(&mut input)
-            .expect("Failed read from stdin");
-        let trimmed_input = input.trim();
+    let mut editor = match LineEditor::new() {
+        Ok(editor) => editor,
+        Err(error) => {
+            eprintln!("Failed to start the line editor: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
 
-        if trimmed_input.is_empty() {
+    // End of input quits like `:q`.
+    while let Some(input) = editor.read(&session) {
+        if input.text().trim().is_empty() {
             continue;
-        } else if trimmed_input.starts_with(":") {
-            match &trimmed_input[1..] {
-                "q" => return,
-                "h" => {
-                    println!("Commands");
-                    println!("--------");
-                    println!("Quit           :q");
-                    println!("Help           :h");
-                    println!("Print metric   :m");
-                    println!();
-                    println!("Expressions");
-                    println!("-----------");
-                    println!(r"Integer:            n");
-                    println!(r"Ratio:              p/q    (q != 0)");
-                    println!(r"Geometric product:  a b");
-                    println!(r"Exteriour product:  a /\ b");
-                    println!(r"Regressive product: a \/ b");
-                    println!(r"Left contraction:   a -| b");
-                    println!(r"Right contraction:  a |- b");
-                    println!(r"Inner product:      a | b");
-                    println!(r"Scalar product:     a * b");
-                    println!(r"Division:           a / b  (b != 0)");
-                    println!(r"Integral Power:     a^n    (n ∈ ℤ)");
-                    println!(r"Negation:           -a");
-                    println!(r"Dualization:        *a");
-                    println!(r"Reversal:           ~a");
-                    println!(r"Conjugate:          !a");
-                    println!(r"Grade Involution:   ^a");
-                    println!(r"Norm:               [a]");
-                }
-                "m" => {
-                    for (i, &square) in metric.0.iter().enumerate() {
-                        println!(
-                            "e{i}^2 = {}",
-                            match square {
-                                metric::Square::Pos => "1",
-                                metric::Square::Neg => "-1",
-                                metric::Square::Zero => "0",
-                            }
-                        )
+        }
+
+        let outcome = session.execute(&input, true);
+        failed_assertion |= outcome.failed_assertion;
+        if outcome.quit {
+            break;
+        }
+
+        println!();
+    }
+
+    editor.save_history();
+
+    if failed_assertion {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// State of a session, shared by the interactive loop, expressions and scripts.
+struct Session {
+    /// Name of the algebra as given to `:metric`.
+    algebra: String,
+    metric: metric::Metric,
+    /// Names of the bases of the metric.
+    notation: Notation,
+    variables: eval::Variables,
+    /// Inputs of the previous results, which are bound to `$1`, `$2`, ...
+    history: Vec<String>,
+    format: Format,
+    /// LaTeX macro of all vectors, such as `\gamma`.
+    latex_basis: Option<String>,
+    /// Syntax of inputs which do not start with a backslash.
+    syntax: Syntax,
+    config: Config,
+    /// Scripts currently being loaded, to detect scripts loading themselves.
+    loading: Vec<PathBuf>,
+}
+
+/// What happened while executing inputs, which determines the exit code.
+#[derive(Default)]
+struct Outcome {
+    syntax_error: bool,
+    undefined: bool,
+    failed_assertion: bool,
+    load_error: bool,
+    quit: bool,
+}
+
+impl Outcome {
+    fn and(self, other: Outcome) -> Outcome {
+        Outcome {
+            syntax_error: self.syntax_error || other.syntax_error,
+            undefined: self.undefined || other.undefined,
+            failed_assertion: self.failed_assertion || other.failed_assertion,
+            load_error: self.load_error || other.load_error,
+            quit: self.quit || other.quit,
+        }
+    }
+
+    fn failed(&self) -> bool {
+        self.syntax_error || self.undefined || self.failed_assertion || self.load_error
+    }
+
+    fn exit_code(&self) -> ExitCode {
+        if self.failed() {
+            ExitCode::FAILURE
+        } else {
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+/// Bases are written with a single digit per vector.
+const MAX_DIMENSION: usize = 9;
+
+const TOO_MANY_DIMENSIONS: &str =
+    "Only dimensions up to 9 are supported due to notational constraints";
+
+/// Parses the argument of a command, which is empty if none is given.
+type ParseArgument = fn(&str) -> Result<Command, String>;
+
+/// All commands by name, which are parsed and completed according to this table.
+const COMMANDS: &[(&str, ParseArgument)] = &[
+    ("q", |argument| without_argument(argument, Command::Quit)),
+    ("h", |argument| without_argument(argument, Command::Help)),
+    ("m", |argument| without_argument(argument, Command::Metric)),
+    ("metric", |metric| match metric {
+        "" => Ok(Command::Metric),
+        metric => parse_metric(metric).map(Command::SetMetric),
+    }),
+    ("basis", |blades| match blades {
+        "" => Ok(Command::Basis),
+        blades => Ok(Command::Orient(
+            blades.split_whitespace().map(str::to_string).collect(),
+        )),
+    }),
+    ("algebras", |argument| {
+        without_argument(argument, Command::Algebras)
+    }),
+    ("embed", |argument| {
+        without_argument(argument, Command::Embed)
+    }),
+    ("drop", |argument| without_argument(argument, Command::Drop)),
+    ("load", |file| {
+        file_argument(file, "load").map(Command::Load)
+    }),
+    ("save", |file| {
+        file_argument(file, "save").map(Command::Save)
+    }),
+    ("restore", |file| {
+        file_argument(file, "restore").map(Command::Restore)
+    }),
+    ("format", parse_format),
+    ("config", |argument| {
+        without_argument(argument, Command::Config)
+    }),
+    ("history", |argument| {
+        without_argument(argument, Command::History)
+    }),
+];
+
+/// Commands start with `:` and are only recognized at the start of an input.
+enum Command {
+    Quit,
+    Help,
+    Metric,
+    SetMetric(Algebra),
+    Basis,
+    /// Names of blades in their preferred orientation, such as `e31`.
+    Orient(Vec<String>),
+    Algebras,
+    Embed,
+    Drop,
+    Load(PathBuf),
+    Save(PathBuf),
+    Restore(PathBuf),
+    Format,
+    /// Format along with the LaTeX macro of vectors, if given.
+    SetFormat(Format, Option<String>),
+    Config,
+    History,
+}
+
+/// Metric along with the names of its bases.
+struct Algebra {
+    /// Name as given to `:metric`, with whitespace normalized.
+    name: String,
+    metric: metric::Metric,
+    notation: Notation,
+}
+
+fn parse_command(command: &str) -> Result<Command, String> {
+    let (name, argument) = command
+        .split_once(char::is_whitespace)
+        .map_or((command, ""), |(name, argument)| (name, argument.trim()));
+    match COMMANDS.iter().find(|&&(command, _)| command == name) {
+        Some((_, parse)) => parse(argument),
+        None => Err("Unknown command. Use :h to see a help screen.".to_string()),
+    }
+}
+
+fn without_argument(argument: &str, command: Command) -> Result<Command, String> {
+    if argument.is_empty() {
+        Ok(command)
+    } else {
+        Err("This command takes no argument. Use :h to see a help screen.".to_string())
+    }
+}
+
+fn file_argument(file: &str, command: &str) -> Result<PathBuf, String> {
+    if file.is_empty() {
+        Err(format!("Missing file, use :{command} FILE"))
+    } else {
+        Ok(PathBuf::from(file))
+    }
+}
+
+/// Format along with the LaTeX macro of vectors, such as `latex align \gamma`.
+fn parse_format(format: &str) -> Result<Command, String> {
+    if format.is_empty() {
+        return Ok(Command::Format);
+    }
+    let (latex_basis, format): (Vec<_>, Vec<_>) = format
+        .split_whitespace()
+        .partition(|word| word.starts_with('\\'));
+    match (format.join(" ").parse()?, &latex_basis[..]) {
+        (format, []) => Ok(Command::SetFormat(format, None)),
+        (format @ (Format::Latex | Format::LatexAlign), [latex_basis]) => {
+            Ok(Command::SetFormat(format, Some(latex_basis.to_string())))
+        }
+        _ => Err("Use e.g. :format latex or :format latex align \\gamma".to_string()),
+    }
+}
+
+/// Named algebras, e.g. `sta` or `pga 3`,
+/// or given by the numbers of positive, negative and degenerate dimensions, e.g. `3,1,0`.
+fn parse_metric(algebra: &str) -> Result<Algebra, String> {
+    let dimensions = |n: &str| {
+        n.trim()
+            .parse::<usize>()
+            .map_err(|_| format!("{n} is not a number of dimensions"))
+    };
+
+    let (metric, notation) = match algebra.split_whitespace().collect_vec()[..] {
+        [name] if preset::preset(name).is_some() => {
+            let preset = preset::preset(name).unwrap();
+            (preset.metric(), preset.notation())
+        }
+        ["pga", n] => {
+            let n = dimensions(n)?;
+            (metric::Metric::pga(n), Notation::pga(n))
+        }
+        [name] if name.starts_with(char::is_alphabetic) => {
+            return Err(format!(
+                "Unknown algebra {name}. Use :algebras to list all algebras."
+            ))
+        }
+        _ => match algebra
+            .split(',')
+            .map(dimensions)
+            .collect::<Result<Vec<_>, _>>()?[..]
+        {
+            [p] => (metric::Metric::signature(p, 0, 0), Notation::default()),
+            [p, q] => (metric::Metric::signature(p, q, 0), Notation::default()),
+            [p, q, r] => (metric::Metric::signature(p, q, r), Notation::default()),
+            _ => return Err("Use e.g. :metric 3,1,0, :metric pga 3 or :metric sta".to_string()),
+        },
+    };
+
+    if metric.dimension() > MAX_DIMENSION {
+        return Err(TOO_MANY_DIMENSIONS.to_string());
+    }
+    Ok(Algebra {
+        name: algebra_name(algebra),
+        metric,
+        notation,
+    })
+}
+
+/// Names blades in the given orientation, such as `e31`.
+fn orient(notation: &mut Notation, dimension: usize, names: &[String]) -> Result<(), String> {
+    for name in names {
+        match notation.blade(name) {
+            Some(vectors) if vectors.iter().all(|&i| i < dimension) => {
+                notation.orient(vectors, name.clone())
+            }
+            _ => {
+                return Err(format!(
+                    "{name} is not a blade of this algebra. \
+                    Use e.g. :basis e31 e021 for e3 e1 and e0 e2 e1"
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Session {
+    /// Session without vectors and variables, with the configured settings.
+    fn new(config: Config) -> Session {
+        Session {
+            algebra: String::new(),
+            metric: metric::Metric::signature(0, 0, 0),
+            notation: Notation::default(),
+            variables: eval::Variables::new(),
+            history: Vec::new(),
+            format: config.format.unwrap_or(Format::Collected),
+            latex_basis: config.latex_basis.clone(),
+            syntax: config.input.unwrap_or(Syntax::Plain),
+            config,
+            loading: Vec::new(),
+        }
+    }
+
+    /// Executes a command or the statements of an input.
+    fn execute(&mut self, input: &Input, interactive: bool) -> Outcome {
+        let Some(command) = input.first_line().strip_prefix(':') else {
+            return self.run(input, interactive);
+        };
+
+        let command = match parse_command(command) {
+            Ok(command) => command,
+            Err(message) => {
+                report_command_error(input, &message, interactive);
+                return Outcome {
+                    syntax_error: true,
+                    ..Default::default()
+                };
+            }
+        };
+
+        match command {
+            Command::Quit => {
+                return Outcome {
+                    quit: true,
+                    ..Default::default()
+                }
+            }
+            Command::Help => print_help(),
+            Command::Metric => {
+                for (i, &square) in self.metric.0.iter().enumerate() {
+                    println!(
+                        "{}^2 = {}",
+                        self.notation.vector(i),
+                        match square {
+                            metric::Square::Pos => "1",
+                            metric::Square::Neg => "-1",
+                            metric::Square::Zero => "0",
+                        }
+                    )
+                }
+            }
+            Command::SetMetric(algebra) => {
+                if let Err(message) = self.set_algebra(algebra) {
+                    report(&format!("  {message}\n"), interactive);
+                }
+                // Scalars are the same in every algebra.
+                let dimension = self.metric.dimension();
+                for value in self.variables.values_mut() {
+                    if value.is_scalar() {
+                        *value = std::mem::take(value).embed(dimension).unwrap();
+                    }
+                }
+
+                let foreign = self.foreign_variables();
+                if !foreign.is_empty() {
+                    report(
+                        &format!(
+                            "  Variables of an algebra of another dimension: {}\n  \
+                            Use :embed to embed them into this algebra or :drop to remove them.\n",
+                            foreign.join(", ")
+                        ),
+                        interactive,
+                    );
+                }
+            }
+            Command::Basis => {
+                let dimension = self.metric.dimension();
+                println!(
+                    "{}",
+                    Basis::all(dimension)
+                        .skip(1)
+                        .sorted_by_key(Basis::grade)
+                        .map(|basis| self.notation.basis(&basis).1)
+                        .join("  ")
+                );
+            }
+            Command::Orient(names) => {
+                let mut notation = self.notation.clone();
+                if let Err(message) = orient(&mut notation, self.metric.dimension(), &names) {
+                    report_command_error(input, &message, interactive);
+                    return Outcome {
+                        syntax_error: true,
+                        ..Default::default()
+                    };
+                }
+                self.notation = notation;
+            }
+            Command::Algebras => {
+                for preset in &preset::PRESETS {
+                    let signature = preset
+                        .metric()
+                        .0
+                        .iter()
+                        .map(|square| match square {
+                            metric::Square::Pos => "+",
+                            metric::Square::Neg => "-",
+                            metric::Square::Zero => "0",
+                        })
+                        .join(" ");
+                    println!(
+                        "{:<16} {:<12} {}",
+                        preset.name,
+                        format!("({signature})"),
+                        preset.description
+                    );
+                }
+                println!(
+                    "{:<16} {:<12} Plane-based geometric algebra of n dimensions",
+                    "pga n", "(+ ... + 0)"
+                );
+            }
+            Command::Embed => {
+                let dimension = self.metric.dimension();
+                for name in self.foreign_variables() {
+                    let value = self.variables[&name].clone();
+                    match value.embed(dimension) {
+                        Some(value) => {
+                            self.variables.insert(name, value);
+                        }
+                        None => report(
+                            &format!("  {name} has vectors this algebra does not have\n"),
+                            interactive,
+                        ),
                     }
                 }
-                _ => {
-                    println!("Unknown command. Use :h to see a help screen.");
+            }
+            Command::Drop => {
+                for name in self.foreign_variables() {
+                    self.variables.remove(&name);
+                }
+            }
+            Command::Load(file) => return self.load(&relative(input, file)),
+            Command::Save(file) => return self.save(&relative(input, file), interactive),
+            Command::Restore(file) => return self.restore(&relative(input, file), interactive),
+            Command::Format => println!("{}", self.format),
+            Command::SetFormat(format, latex_basis) => {
+                self.format = format;
+                if latex_basis.is_some() {
+                    self.latex_basis = latex_basis;
                 }
             }
+            Command::Config => self.print_config(),
+            Command::History => {
+                for (i, source) in self.history.iter().enumerate() {
+                    let name = format!("${}", i + 1);
+                    let result = self.variables[&name].clone();
+                    println!("{name:<4} {source}");
+                    println!("{:<4} = {}", "", self.format_result(result));
+                }
+            }
+        }
+        Outcome::default()
+    }
+
+    /// Switches to another algebra, whose blades are oriented as configured.
+    fn set_algebra(&mut self, algebra: Algebra) -> Result<(), String> {
+        self.algebra = algebra.name;
+        self.metric = algebra.metric;
+        self.notation = algebra.notation;
+
+        match self.config.basis.get(&self.algebra) {
+            Some(names) => orient(&mut self.notation, self.metric.dimension(), names)
+                .map_err(|message| format!("Configured basis of {}: {message}", self.algebra)),
+            None => Ok(()),
+        }
+    }
+
+    /// Prints the effective settings, where options and commands override the configuration.
+    fn print_config(&self) {
+        let config = &self.config;
+        let files = config
+            .files
+            .iter()
+            .map(|file| file.display().to_string())
+            .join(", ");
+        println!(
+            "Files    {}",
+            if files.is_empty() { "none" } else { &files }
+        );
+        println!("Algebra  {}", self.algebra);
+        println!("Format   {}", self.format);
+        if let Some(latex_basis) = &self.latex_basis {
+            println!("Vectors  {latex_basis}");
+        }
+        println!("Input    {}", self.syntax);
+        for (algebra, names) in &config.basis {
+            println!("Basis    {algebra}: {}", names.join(" "));
+        }
+        for script in &config.preload {
+            println!("Preload  {}", script.display());
+        }
+    }
+
+    /// Names of variables assigned in an algebra of another dimension than the current one.
+    /// Previous results remain as they are.
+    fn foreign_variables(&self) -> Vec<String> {
+        let dimension = self.metric.dimension();
+        self.variables
+            .iter()
+            .filter(|(name, _)| !eval::is_result(name))
+            .filter(|(_, value)| value.dimension().is_some_and(|other| other != dimension))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Executes all statements of the input and prints their results.
+    /// Interactively, results are indented and errors are printed along with them,
+    /// otherwise errors are printed to stderr.
+    fn run(&mut self, input: &Input, interactive: bool) -> Outcome {
+        let (indent, equals) = if interactive {
+            ("  ", "  = ")
         } else {
-            let expr = match parse::parse(&input) {
-                Some(expr) => expr,
-                None => {
-                    println!();
-                    continue;
+            ("", "")
+        };
+
+        let mut outcome = Outcome::default();
+        let text = input.text();
+        let syntax = self.syntax.of(&text);
+        let statements = match parse::parse_statements(&text, &self.notation, syntax) {
+            Ok(statements) => statements,
+            Err(errors) => {
+                report_syntax_errors(input, &errors, interactive);
+                outcome.syntax_error = true;
+                return outcome;
+            }
+        };
+        if syntax == Syntax::Plain {
+            report_spacing_hints(input, interactive);
+        }
+
+        for statement in statements {
+            let source = match &statement {
+                Statement::Expr(Spanned(_, span)) => {
+                    text.chars().skip(span.start).take(span.len()).collect()
+                }
+                _ => String::new(),
+            };
+            // Results are listed along with their expressions in `align` blocks.
+            let written = match &statement {
+                Statement::Expr(expr) if self.format == Format::LatexAlign => {
+                    Some(expr.0.latex(&self.latex()))
+                }
+                Statement::Assign(name, _) if self.format == Format::LatexAlign => {
+                    Some(self.latex().symbol(name, 1))
                 }
+                _ => None,
             };
 
-            match eval::eval(expr, &(&metric)) {
-                Ok(result) => {
-                    println!("  = {}", result.optimize());
+            match eval::exec(statement, &self.metric, &mut self.variables) {
+                Ok(eval::Value::Polynomial(result)) => {
+                    self.record(source, &result);
+                    match written {
+                        Some(written) => print!("{}", align(&written, &self.format_result(result))),
+                        None => println!("{equals}{}", self.format_result(result)),
+                    }
+                }
+                Ok(eval::Value::Boolean(result)) => println!("{equals}{result}"),
+                Ok(eval::Value::Assertion(true)) => {}
+                Ok(eval::Value::Assertion(false)) => {
+                    outcome.failed_assertion = true;
+                    println!("{indent}Assertion failed");
                 }
-                Err(eval::Undefined(spans)) => {
-                    let mut end = 0;
-                    for span in spans {
-                        (end..span.start).for_each(|_| print!(" "));
-                        end = span.end;
-                        span.for_each(|_| print!("^"));
+                Ok(eval::Value::Assignment(name, value)) => {
+                    if let Some(echo) = self.echo_assignment(&name, value, written, interactive) {
+                        print!("{echo}");
                     }
-                    println!();
-                    println!("  = _|_");
                 }
+                Err(causes) => {
+                    outcome.undefined = true;
+                    report(
+                        &describe_undefined(input, &causes, !interactive),
+                        interactive,
+                    );
+                    println!("{equals}_|_");
+                }
+            }
+        }
+        outcome
+    }
+
+    /// Assignments are echoed interactively, and listed in `align` blocks in any case,
+    /// so that these show all equations of a script or piped input.
+    fn echo_assignment(
+        &self,
+        name: &str,
+        value: Polynomial,
+        written: Option<String>,
+        interactive: bool,
+    ) -> Option<String> {
+        match written {
+            Some(written) => Some(align(&written, &self.format_result(value))),
+            None if interactive => Some(format!("  {name} = {}\n", self.format_result(value))),
+            None => None,
+        }
+    }
+
+    fn format_result(&self, result: Polynomial) -> String {
+        match self.format {
+            Format::Raw => result.format(&self.notation),
+            Format::Collected => result.collected(&self.notation).to_string(),
+            Format::Latex | Format::LatexAlign => result.collected(&self.latex()).to_string(),
+        }
+    }
+
+    fn latex(&self) -> Latex<'_> {
+        Latex::new(&self.notation, self.latex_basis.as_deref())
+    }
+
+    /// Binds a result to `ans` and to the next of `$1`, `$2`, ...
+    fn record(&mut self, source: String, result: &Polynomial) {
+        self.history.push(source);
+        let name = format!("${}", self.history.len());
+        self.variables.insert(name, result.clone());
+        self.variables.insert("ans".to_string(), result.clone());
+    }
+
+    /// Executes a script within this session.
+    fn load(&mut self, path: &Path) -> Outcome {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if self.loading.contains(&canonical) {
+            eprintln!("{} is already being loaded", path.display());
+            return Outcome {
+                load_error: true,
+                ..Default::default()
             };
         }
 
-        println!();
+        // Scripts and saved sessions are written in plain syntax regardless of `--input`.
+        self.loading.push(canonical);
+        let syntax = std::mem::replace(&mut self.syntax, Syntax::Plain);
+        let outcome = script::run(self, path);
+        self.syntax = syntax;
+        self.loading.pop();
+        outcome
+    }
+
+    /// Writes the algebra and the variables as a script, which `:restore` executes to continue.
+    /// Variables of another algebra and previous results are not saved.
+    fn save(&self, path: &Path, interactive: bool) -> Outcome {
+        let mut script = format!("# Saved galc session\n:metric {}\n", self.algebra);
+
+        // Blades oriented other than in the algebra itself.
+        let dimension = self.metric.dimension();
+        if let Ok(algebra) = parse_metric(&self.algebra) {
+            let oriented = Basis::all(dimension)
+                .filter(|basis| self.notation.basis(basis) != algebra.notation.basis(basis))
+                .map(|basis| self.notation.basis(&basis).1)
+                .collect_vec();
+            if !oriented.is_empty() {
+                script.push_str(&format!(":basis {}\n", oriented.join(" ")));
+            }
+        }
+
+        // Each variable is assigned before those whose names its value contains,
+        // which would otherwise be substituted when restoring, as in `r = a e1` and `a = 2`.
+        let foreign = self.foreign_variables();
+        let mut unsaved = self
+            .variables
+            .iter()
+            .filter(|(name, _)| !eval::is_result(name) && !foreign.contains(name))
+            .collect_vec();
+        while !unsaved.is_empty() {
+            let next = unsaved
+                .iter()
+                .position(|(name, _)| {
+                    !unsaved.iter().any(|(other, value)| {
+                        other != name && value.names().contains(name.as_str())
+                    })
+                })
+                .unwrap_or(0);
+            let (name, value) = unsaved.remove(next);
+            let value = value.collected(&self.notation);
+            script.push_str(&format!("{name} = {value}\n"));
+        }
+        if !foreign.is_empty() {
+            report(
+                &format!(
+                    "  Not saved, since assigned in an algebra of another dimension: {}\n",
+                    foreign.join(", ")
+                ),
+                interactive,
+            );
+        }
+
+        match fs::write(path, script) {
+            Ok(()) => Outcome::default(),
+            Err(error) => {
+                report(
+                    &format!("  Cannot write {}: {error}\n", path.display()),
+                    interactive,
+                );
+                Outcome {
+                    load_error: true,
+                    ..Default::default()
+                }
+            }
+        }
+    }
+
+    /// Replaces the variables and results of this session by those of a saved one.
+    fn restore(&mut self, path: &Path, interactive: bool) -> Outcome {
+        if !path.is_file() {
+            report(
+                &format!("  There is no saved session {}\n", path.display()),
+                interactive,
+            );
+            return Outcome {
+                load_error: true,
+                ..Default::default()
+            };
+        }
+
+        self.variables.clear();
+        self.history.clear();
+        self.load(path)
+    }
+}
+
+/// Whether the argument names a script rather than being an expression.
+/// Scripts are recognized by their extension alone, so that `galc a` evaluates `a`
+/// regardless of the files in the working directory.
+fn is_script(argument: &str) -> bool {
+    Path::new(argument).extension() == Some("ga".as_ref())
+}
+
+/// Scripts refer to files relative to their own location.
+fn relative(input: &Input, file: PathBuf) -> PathBuf {
+    let directory = input
+        .location()
+        .and_then(|location| Path::new(&location.file).parent());
+    match directory {
+        Some(directory) => directory.join(file),
+        None => file,
+    }
+}
+
+/// Checks the syntax of an input without executing it.
+fn check(input: &Input, notation: &Notation, syntax: Syntax) -> Outcome {
+    let valid = match input.first_line().strip_prefix(':') {
+        Some(command) => parse_command(command)
+            .map(|_| ())
+            .map_err(|message| report_command_error(input, &message, false)),
+        None => {
+            let text = input.text();
+            let syntax = syntax.of(&text);
+            parse::parse_statements(&text, notation, syntax)
+                .map(|_| {
+                    if syntax == Syntax::Plain {
+                        report_spacing_hints(input, false);
+                    }
+                })
+                .map_err(|errors| report_syntax_errors(input, &errors, false))
+        }
+    };
+    Outcome {
+        syntax_error: valid.is_err(),
+        ..Default::default()
+    }
+}
+
+/// Prints diagnostics along with the results interactively, and to stderr otherwise.
+fn report(text: &str, interactive: bool) {
+    if interactive {
+        print!("{text}")
+    } else {
+        eprint!("{text}")
+    }
+}
+
+fn report_syntax_errors(input: &Input, errors: &[SyntaxError], interactive: bool) {
+    for error in errors {
+        report(
+            &input.underline(std::slice::from_ref(&error.span), !interactive),
+            interactive,
+        );
+        report(&describe_syntax_error(error), interactive);
+    }
+}
+
+/// Hints do not prevent the input from being evaluated.
+fn report_spacing_hints(input: &Input, interactive: bool) {
+    for Spanned(hint, span) in parse::spacing_hints(&input.text()) {
+        report(&input.underline(&[span], !interactive), interactive);
+        report(&format!("  Hint: {hint}\n"), interactive);
+    }
+}
+
+fn report_command_error(input: &Input, message: &str, interactive: bool) {
+    let command = 0..input.text().chars().count();
+    report(&input.underline(&[command], !interactive), interactive);
+    report(&format!("  {message}\n"), interactive);
+}
+
+fn print_help() {
+    println!("Commands");
+    println!("--------");
+    println!("Quit           :q");
+    println!("Help           :h");
+    println!("Print metric   :m");
+    println!("Change metric  :metric 3,1,0  :metric pga 3  :metric sta");
+    println!("Print bases    :basis");
+    println!("Orient bases   :basis e31 e021 (printed as e3 e1 and e0 e2 e1)");
+    println!("List algebras  :algebras");
+    println!("Keep variables :embed (into the changed metric)  :drop");
+    println!("Load script    :load FILE");
+    println!("Save session   :save FILE  (variables and metric as a script)");
+    println!("Resume session :restore FILE");
+    println!("List results   :history");
+    println!("Change format  :format latex  :format latex align \\gamma  :format collected");
+    println!("Show settings  :config (of ~/.config/galc/config.toml and .galc.toml)");
+    println!();
+    println!("Ctrl-R searches the history, Ctrl-C discards the input and Ctrl-D quits.");
+    println!("Input continues on the next line while parentheses are open.");
+    println!();
+    println!("Expressions");
+    println!("-----------");
+    println!(r"Integer:            n");
+    println!(r"Ratio:              p/q    (q != 0)");
+    println!(r"Decimal:            1.5e-3");
+    println!(r"Geometric product:  a b");
+    println!(r"Exteriour product:  a /\ b");
+    println!(r"Regressive product: a \/ b");
+    println!(r"Left contraction:   a -| b");
+    println!(r"Right contraction:  a |- b");
+    println!(r"Inner product:      a | b");
+    println!(r"Scalar product:     a * b");
+    println!(r"Division:           a / b  (b != 0)");
+    println!(r"Power:              a^n    (n ∈ ℤ or symbolic)");
+    println!(r"                    a^(n + 1)");
+    println!(r"Negation:           -a");
+    println!(r"Dualization:        *a");
+    println!(r"Reversal:           ~a");
+    println!(r"Conjugate:          !a");
+    println!(r"Grade Involution:   ^a");
+    println!(r"Norm:               [a]");
+    println!();
+    println!("Unicode notation: ∧ ∨ ⌋ ⌊ · ⋆ a† a² a⁻¹ 𝐼");
+    println!(
+        r"LaTeX notation:   \mathbf{{e}}_{{12}} \wedge a  \tilde{{R}}  \frac{{1}}{{2}}  \langle A \rangle_2  A^{{*}}"
+    );
+    println!(
+        "                  (inputs starting with a backslash, or all inputs with --input latex)"
+    );
+    println!();
+    println!("Statements");
+    println!("----------");
+    println!(r"Assignment:         a = e1 + e2");
+    println!(r"Previous results:   ans  $1  $2 ...");
+    println!(r"Equality:           a == b");
+    println!(r"Inequality:         a != b");
+    println!(r"Assertion:          assert a == b");
+    println!(r"Sequence:           a; b");
+    println!(r"Comment:            a  # comment");
+    println!(r"Continuation:       a + \  (continued on the next line)");
+}
+
+fn align(written: &str, result: &str) -> String {
+    format!("\\begin{{align}}\n  {written} &= {result}\n\\end{{align}}\n")
+}
+
+fn describe_syntax_error(error: &SyntaxError) -> String {
+    let mut description = format!("  Syntax error: {}\n", error.message);
+    if let Some(hint) = &error.hint {
+        description.push_str(&format!("  Hint: {hint}\n"));
+    }
+    description
+}
+
+fn describe_undefined(input: &Input, causes: &[Spanned<eval::Undefined>], echo: bool) -> String {
+    let spans = causes.iter().map(|cause| cause.1.clone()).collect_vec();
+    let mut description = input.underline(&spans, echo);
+    for cause in causes {
+        description.push_str(&format!("  {}\n", cause.0));
     }
+    description
 }
@@ -0,0 +1,157 @@
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use itertools::Itertools;
+use serde::Deserialize;
+
+use crate::parse::Syntax;
+
+#[cfg(test)]
+mod test;
+
+/// Settings read from `~/.config/galc/config.toml` and `.galc.toml` in the working directory,
+/// where the latter takes precedence. Command line options override both.
+///
+/// ```toml
+/// algebra = "pga 3"
+/// format = "latex align"
+/// latex_basis = '\gamma'
+/// input = "latex"
+/// preload = ["definitions.ga"]
+///
+/// [basis]
+/// "pga 3" = ["e13"]
+/// ```
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Algebra as given to `:metric`, such as `sta`, `pga 3` or `3,1,0`.
+    pub algebra: Option<String>,
+    pub format: Option<Format>,
+    /// LaTeX macro of all vectors, such as `\gamma`.
+    pub latex_basis: Option<String>,
+    /// Syntax of typed and piped inputs, such as `latex`. Scripts are always plain.
+    pub input: Option<Syntax>,
+    /// Preferred orientation of blades by algebra, such as `e31` in `pga 3`.
+    pub basis: BTreeMap<String, Vec<String>>,
+    /// Scripts executed at the start of every session.
+    pub preload: Vec<PathBuf>,
+    /// Files the settings were read from.
+    #[serde(skip)]
+    pub files: Vec<PathBuf>,
+}
+
+/// How results are printed.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "String")]
+pub enum Format {
+    /// Monomials collected by basis.
+    Collected,
+    /// Monomials as they are.
+    Raw,
+    /// Monomials collected by basis as LaTeX.
+    Latex,
+    /// LaTeX `align` blocks of the inputs and their results.
+    LatexAlign,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Format, String> {
+        match format.split_whitespace().collect::<Vec<_>>()[..] {
+            ["collected"] => Ok(Format::Collected),
+            ["raw"] => Ok(Format::Raw),
+            ["latex"] => Ok(Format::Latex),
+            ["latex", "align"] => Ok(Format::LatexAlign),
+            _ => Err(format!(
+                "{format} is not a format, use collected, raw, latex or latex align"
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for Format {
+    type Error = String;
+
+    fn try_from(format: String) -> Result<Format, String> {
+        format.parse()
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::Collected => write!(f, "collected"),
+            Format::Raw => write!(f, "raw"),
+            Format::Latex => write!(f, "latex"),
+            Format::LatexAlign => write!(f, "latex align"),
+        }
+    }
+}
+
+impl Config {
+    /// Reads the user's and the project's settings, either of which may not exist.
+    pub fn load() -> Result<Config, String> {
+        // `~/.config` on every platform, as documented, rather than the platform's own directory.
+        let user = dirs::home_dir().map(|home| home.join(".config/galc/config.toml"));
+        let project = PathBuf::from(".galc.toml");
+
+        let mut config = Config::default();
+        for path in user.into_iter().chain([project]) {
+            if path.is_file() {
+                config = config.and(Config::read(&path)?);
+            }
+        }
+        Ok(config)
+    }
+
+    fn read(path: &Path) -> Result<Config, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("Cannot read {}: {error}", path.display()))?;
+        let mut config: Config =
+            toml::from_str(&text).map_err(|error| format!("{}: {error}", path.display()))?;
+
+        config.basis = config
+            .basis
+            .into_iter()
+            .map(|(algebra, names)| (algebra_name(&algebra), names))
+            .collect();
+
+        // Scripts are preloaded relative to the file naming them.
+        if let Some(directory) = path.parent() {
+            for script in &mut config.preload {
+                *script = directory.join(&*script);
+            }
+        }
+        config.files.push(path.to_path_buf());
+        Ok(config)
+    }
+
+    /// Settings of both, where the other one takes precedence.
+    /// Scripts of both are preloaded.
+    fn and(mut self, other: Config) -> Config {
+        self.basis.extend(other.basis);
+        self.preload.extend(other.preload);
+        self.files.extend(other.files);
+        Config {
+            algebra: other.algebra.or(self.algebra),
+            format: other.format.or(self.format),
+            latex_basis: other.latex_basis.or(self.latex_basis),
+            input: other.input.or(self.input),
+            ..self
+        }
+    }
+}
+
+/// Name of an algebra as given to `:metric`, regardless of the whitespace within it.
+pub fn algebra_name(algebra: &str) -> String {
+    algebra
+        .split(',')
+        .map(|part| part.split_whitespace().join(" "))
+        .join(",")
+}
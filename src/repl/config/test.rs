@@ -0,0 +1,73 @@
+use std::{env, fs, path::PathBuf, process};
+
+use super::{algebra_name, Config, Format};
+use crate::parse::Syntax;
+
+#[test]
+fn precedence() {
+    let user = Config {
+        algebra: Some("sta".to_string()),
+        format: Some(Format::Latex),
+        latex_basis: Some(r"\gamma".to_string()),
+        basis: [("pga 3".to_string(), vec!["e13".to_string()])].into(),
+        preload: vec![PathBuf::from("user.ga")],
+        ..Default::default()
+    };
+    let project = Config {
+        algebra: Some("pga 3".to_string()),
+        input: Some(Syntax::Latex),
+        basis: [("pga 3".to_string(), vec!["e31".to_string()])].into(),
+        preload: vec![PathBuf::from("project.ga")],
+        ..Default::default()
+    };
+
+    let config = user.and(project);
+    assert_eq!(config.algebra.as_deref(), Some("pga 3"));
+    assert_eq!(config.format, Some(Format::Latex));
+    assert_eq!(config.latex_basis.as_deref(), Some(r"\gamma"));
+    assert_eq!(config.input, Some(Syntax::Latex));
+    assert_eq!(config.basis["pga 3"], ["e31"]);
+    assert_eq!(
+        config.preload,
+        [PathBuf::from("user.ga"), PathBuf::from("project.ga")]
+    );
+}
+
+#[test]
+fn algebra_names() {
+    assert_eq!(algebra_name("pga 3"), "pga 3");
+    assert_eq!(algebra_name("  pga   3 "), "pga 3");
+    assert_eq!(algebra_name("3, 1 ,0"), "3,1,0");
+    assert_eq!(algebra_name("sta"), "sta");
+}
+
+#[test]
+fn files() {
+    let directory = env::temp_dir().join(format!("galc-config-{}", process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("config.toml");
+    fs::write(
+        &path,
+        "algebra = \"pga 3\"\npreload = [\"definitions.ga\"]\n\n[basis]\n\"pga  3\" = [\"e31\"]\n",
+    )
+    .unwrap();
+
+    let config = Config::read(&path);
+    fs::write(&path, "algebra = 3\n").unwrap();
+    let invalid = Config::read(&path);
+    fs::write(&path, "colour = \"blue\"\n").unwrap();
+    let unknown = Config::read(&path);
+    fs::remove_dir_all(&directory).unwrap();
+
+    let config = config.unwrap();
+    assert_eq!(config.algebra.as_deref(), Some("pga 3"));
+    assert_eq!(config.basis["pga 3"], ["e31"]);
+    // Scripts are preloaded relative to the file naming them.
+    assert_eq!(config.preload, [directory.join("definitions.ga")]);
+
+    assert!(invalid
+        .unwrap_err()
+        .starts_with(&path.display().to_string()));
+    assert!(unknown.unwrap_err().contains("colour"));
+    assert_eq!(config.files, [path]);
+}
@@ -0,0 +1,192 @@
+use std::path::PathBuf;
+
+use itertools::Itertools;
+use rustyline::{
+    completion::Completer,
+    error::ReadlineError,
+    history::DefaultHistory,
+    validate::{ValidationContext, ValidationResult, Validator},
+    Context, Editor, Helper, Highlighter, Hinter,
+};
+
+use crate::{algebra::basis::Basis, parse};
+
+use super::{input::Input, Session, COMMANDS};
+
+/// Line editor of the interactive mode, whose history persists across sessions.
+pub struct LineEditor {
+    editor: Editor<EditorHelper, DefaultHistory>,
+    history: Option<PathBuf>,
+}
+
+#[derive(Helper, Highlighter, Hinter)]
+struct EditorHelper {
+    /// Names bound in the session.
+    variables: Vec<String>,
+    /// Names of the vectors and blades of the metric.
+    bases: Vec<String>,
+}
+
+impl Completer for EditorHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        position: usize,
+        _: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(complete(line, position, &self.variables, &self.bases))
+    }
+}
+
+/// Completes commands after `:`, and otherwise reserved names, variables and bases.
+/// Returns where the completed word starts along with the candidates.
+pub fn complete(
+    line: &str,
+    position: usize,
+    variables: &[String],
+    bases: &[String],
+) -> (usize, Vec<String>) {
+    let start = line[..position]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| unicode_ident::is_xid_continue(c))
+        .last()
+        .map_or(position, |(i, _)| i);
+    let word = &line[start..position];
+
+    let names = match line[..start].trim_start() {
+        ":" => COMMANDS
+            .iter()
+            .map(|(command, _)| command.to_string())
+            .collect_vec(),
+        // Arguments of commands are not completed.
+        preceding if preceding.starts_with(':') => Vec::new(),
+        _ => parse::RESERVED
+            .iter()
+            .map(|name| name.to_string())
+            .chain(variables.iter().cloned())
+            .chain(bases.iter().cloned())
+            .collect_vec(),
+    };
+
+    let candidates = names
+        .into_iter()
+        .filter(|name| name.starts_with(word))
+        .sorted()
+        .dedup()
+        .collect();
+    (start, candidates)
+}
+
+impl Validator for EditorHelper {
+    fn validate(&self, context: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        if is_complete(context.input()) {
+            Ok(ValidationResult::Valid(None))
+        } else {
+            Ok(ValidationResult::Incomplete)
+        }
+    }
+}
+
+/// Input is continued on the next line while delimiters are unbalanced or a line ends in `\`.
+/// Commands are always complete.
+pub fn is_complete(text: &str) -> bool {
+    let input = Input::new(text);
+    if input.first_line().starts_with(':') {
+        return true;
+    }
+
+    let mut depth = 0;
+    for line in text.lines() {
+        let code = line.split_once('#').map_or(line, |(code, _)| code);
+        for c in code.chars() {
+            match c {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+
+    depth <= 0 && !input.is_continued()
+}
+
+impl LineEditor {
+    pub fn new() -> rustyline::Result<LineEditor> {
+        let mut editor = Editor::new()?;
+        editor.set_helper(Some(EditorHelper {
+            variables: Vec::new(),
+            bases: Vec::new(),
+        }));
+
+        let history = dirs::data_dir().map(|directory| directory.join("galc").join("history"));
+        if let Some(history) = &history {
+            // There is no history yet on the first start.
+            let _ = editor.load_history(history);
+        }
+
+        Ok(LineEditor { editor, history })
+    }
+
+    /// Reads an input, which may span several lines.
+    /// Names are completed according to the current state of the session.
+    /// Ctrl-C discards the current input, and nothing is read at the end of input.
+    pub fn read(&mut self, session: &Session) -> Option<Input> {
+        if let Some(helper) = self.editor.helper_mut() {
+            // Numbered results are not completed, since `$` does not start a name.
+            helper.variables = session
+                .variables
+                .keys()
+                .filter(|name| !name.starts_with('$'))
+                .cloned()
+                .collect();
+            helper.bases = bases(session);
+        }
+
+        // The prompt shows the number of the next result.
+        let prompt = format!("${}> ", session.history.len() + 1);
+        loop {
+            match self.editor.readline(&prompt) {
+                Ok(text) => {
+                    if !text.trim().is_empty() {
+                        let _ = self.editor.add_history_entry(text.as_str());
+                    }
+                    return Some(Input::new(&text).prompted(&prompt));
+                }
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => return None,
+                Err(error) => {
+                    eprintln!("Failed to read input: {error}");
+                    return None;
+                }
+            }
+        }
+    }
+
+    pub fn save_history(&mut self) {
+        let Some(history) = &self.history else {
+            return;
+        };
+        let saved = match history.parent() {
+            Some(directory) => std::fs::create_dir_all(directory).map_err(ReadlineError::from),
+            None => Ok(()),
+        }
+        .and_then(|_| self.editor.save_history(history));
+        if let Err(error) = saved {
+            eprintln!("Failed to save history to {}: {error}", history.display());
+        }
+    }
+}
+
+/// Names of all bases which can be written as a single word, such as `e12` or `k`.
+pub fn bases(session: &Session) -> Vec<String> {
+    let dimension = session.metric.dimension();
+    let vectors = (0..dimension).map(|i| session.notation.vector(i));
+    let blades = Basis::all(dimension)
+        .skip(1)
+        .map(|basis| session.notation.basis(&basis).1)
+        .filter(|name| !name.contains(' '));
+    vectors.chain(blades).collect()
+}
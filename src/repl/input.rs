@@ -0,0 +1,175 @@
+use itertools::Itertools;
+
+use crate::parse::span::Span;
+
+/// Input consisting of one or more lines, where a line ending in `\` continues on the next line.
+pub struct Input {
+    lines: Vec<String>,
+    location: Option<Location>,
+    /// Width of the prompt the input was typed after.
+    prompt: usize,
+}
+
+/// Position of the first line of an input within a script file.
+#[derive(Clone)]
+pub struct Location {
+    pub file: String,
+    pub line: usize,
+}
+
+impl Input {
+    pub fn new(text: &str) -> Input {
+        Input {
+            lines: text.lines().map(str::to_string).collect(),
+            location: None,
+            prompt: 0,
+        }
+    }
+
+    /// Input typed after a prompt, which offsets the positions of its first line.
+    pub fn prompted(self, prompt: &str) -> Input {
+        Input {
+            prompt: prompt.chars().count(),
+            ..self
+        }
+    }
+
+    pub fn push(&mut self, line: &str) {
+        self.lines.extend(line.lines().map(str::to_string));
+    }
+
+    pub fn is_continued(&self) -> bool {
+        self.lines.last().is_some_and(|line| is_continued(line))
+    }
+
+    /// The first line without surrounding whitespace, which may be a command.
+    pub fn first_line(&self) -> &str {
+        self.lines.first().map_or("", |line| line.trim())
+    }
+
+    /// Lines joined by newlines, with the continuing backslashes replaced by spaces,
+    /// so that character positions of the text and of the lines correspond.
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| match line.trim_end().strip_suffix('\\') {
+                Some(continued) if is_continued(line) => {
+                    format!("{continued} {}", &line[continued.len() + 1..])
+                }
+                _ => line.clone(),
+            })
+            .join("\n")
+    }
+
+    pub fn location(&self) -> Option<&Location> {
+        self.location.as_ref()
+    }
+
+    /// Underlines the given spans of the text.
+    /// The lines are echoed if requested or if they span more than one line,
+    /// otherwise only the carets are printed below the line as typed by the user.
+    /// Inputs of scripts are preceded by the file, line and column of the first span.
+    pub fn underline(&self, spans: &[Span], echo: bool) -> String {
+        if !echo && self.lines.len() <= 1 {
+            return format!("{}{}\n", " ".repeat(self.prompt), carets(spans));
+        }
+
+        let mut underlined = String::new();
+        if let (Some(location), Some(span)) = (&self.location, spans.first()) {
+            let (line, column) = self.line_and_column(span.start);
+            underlined.push_str(&format!(
+                "{}:{}:{}\n",
+                location.file,
+                location.line + line,
+                column + 1
+            ));
+        }
+
+        let mut start = 0;
+        for line in &self.lines {
+            let end = start + line.chars().count();
+            // Errors at the end of a line are located just past its last character.
+            let line_spans = spans
+                .iter()
+                .filter(|span| span.start <= end && span.end > start)
+                .map(|span| {
+                    let span_start = span.start.max(start);
+                    span_start - start..span.end.min(end).max(span_start + 1) - start
+                })
+                .collect_vec();
+            underlined.push_str(&format!("{line}\n"));
+            if !line_spans.is_empty() {
+                underlined.push_str(&format!("{}\n", carets(&line_spans)));
+            }
+            start = end + 1;
+        }
+        underlined
+    }
+
+    /// Zero-based line and column of a character position of the text.
+    fn line_and_column(&self, position: usize) -> (usize, usize) {
+        let mut start = 0;
+        for (i, line) in self.lines.iter().enumerate() {
+            let end = start + line.chars().count();
+            if position <= end {
+                return (i, position - start);
+            }
+            start = end + 1;
+        }
+        (self.lines.len().saturating_sub(1), 0)
+    }
+}
+
+/// Groups the lines of a script into inputs, joining continued lines.
+/// Lines are consumed lazily, so that inputs can be executed as they arrive.
+pub struct Inputs<I> {
+    lines: std::iter::Enumerate<I>,
+    file: String,
+}
+
+impl<I: Iterator<Item = String>> Inputs<I> {
+    pub fn new(lines: I, file: &str) -> Inputs<I> {
+        Inputs {
+            lines: lines.enumerate(),
+            file: file.to_string(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = String>> Iterator for Inputs<I> {
+    type Item = Input;
+
+    fn next(&mut self) -> Option<Input> {
+        let (i, line) = self.lines.next()?;
+        let mut input = Input::new(&line);
+        input.location = Some(Location {
+            file: self.file.clone(),
+            line: i + 1,
+        });
+        while input.is_continued() {
+            let Some((_, line)) = self.lines.next() else {
+                break;
+            };
+            input.push(&line);
+        }
+        Some(input)
+    }
+}
+
+/// A trailing `\` continues the line, unless it ends a `/\` or a comment.
+fn is_continued(line: &str) -> bool {
+    let line = line.trim_end();
+    line.ends_with('\\') && !line.ends_with(r"/\") && !line.contains('#')
+}
+
+/// Underlines the given spans of a single line.
+fn carets(spans: &[Span]) -> String {
+    let mut carets = String::new();
+    let mut end = 0;
+    for span in spans {
+        (end..span.start).for_each(|_| carets.push(' '));
+        end = end.max(span.end);
+        span.clone().for_each(|_| carets.push('^'));
+    }
+    carets
+}
@@ -0,0 +1,92 @@
+use std::{fs, io::stdin, path::Path};
+
+use crate::{algebra::notation::Notation, parse::Syntax};
+
+use super::{check as check_input, input::Inputs, Outcome, Session};
+
+/// Executes the statements and commands of a script one input after another,
+/// printing results without the decoration of the interactive mode.
+/// `:q` ends the script early.
+pub fn run(session: &mut Session, path: &Path) -> Outcome {
+    let Some(text) = read(path) else {
+        return Outcome {
+            load_error: true,
+            ..Default::default()
+        };
+    };
+
+    let lines = text.lines().map(str::to_string);
+    Outcome {
+        quit: false,
+        ..run_lines(session, lines, &path.display().to_string())
+    }
+}
+
+/// Executes lines as they are read, until they end or `:q` is read.
+fn run_lines(session: &mut Session, lines: impl Iterator<Item = String>, file: &str) -> Outcome {
+    let mut outcome = Outcome::default();
+    for input in Inputs::new(lines, file) {
+        outcome = outcome.and(session.execute(&input, false));
+        if outcome.quit {
+            break;
+        }
+    }
+    outcome
+}
+
+/// Executes piped input like a script, or only checks its syntax.
+/// Input which is not valid UTF-8 ends it with a load error.
+pub fn run_stdin(session: &mut Session, check: bool) -> Outcome {
+    let file = "<stdin>";
+    let mut error = None;
+    let lines = stdin()
+        .lines()
+        .map_while(|line| line.map_err(|cause| error = Some(cause)).ok());
+    let outcome = if check {
+        check_lines(lines, file, &session.notation, session.syntax)
+    } else {
+        run_lines(session, lines, file)
+    };
+
+    match error {
+        Some(error) => {
+            eprintln!("Cannot read {file}: {error}");
+            Outcome {
+                load_error: true,
+                ..outcome
+            }
+        }
+        None => outcome,
+    }
+}
+
+/// Checks the syntax of all statements and commands of a script without executing them.
+/// Scripts loaded by the script are not checked.
+pub fn check(path: &Path, notation: &Notation) -> Outcome {
+    let Some(text) = read(path) else {
+        return Outcome {
+            load_error: true,
+            ..Default::default()
+        };
+    };
+
+    let lines = text.lines().map(str::to_string);
+    check_lines(lines, &path.display().to_string(), notation, Syntax::Plain)
+}
+
+fn check_lines(
+    lines: impl Iterator<Item = String>,
+    file: &str,
+    notation: &Notation,
+    syntax: Syntax,
+) -> Outcome {
+    Inputs::new(lines, file)
+        .map(|input| check_input(&input, notation, syntax))
+        .fold(Outcome::default(), Outcome::and)
+}
+
+fn read(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .map_err(|error| eprintln!("Cannot read {}: {error}", path.display()))
+        .ok()
+}
@@ -0,0 +1,154 @@
+use std::{env, fs, process};
+
+use super::{
+    config::{Config, Format},
+    editor::{bases, complete, is_complete},
+    input::Input,
+    is_script, parse_command, parse_metric, Session, COMMANDS,
+};
+use crate::parse::Syntax;
+
+#[test]
+fn script_arguments() {
+    assert!(is_script("script.ga"));
+    assert!(is_script("scripts/rotors.ga"));
+    assert!(!is_script("a"));
+    assert!(!is_script("a.b"));
+    assert!(!is_script("e1 + e2"));
+}
+
+#[test]
+fn continued_inputs() {
+    assert!(is_complete("a + b"));
+    assert!(is_complete(""));
+    assert!(!is_complete("(a + b"));
+    assert!(!is_complete("[a"));
+    assert!(!is_complete(r"\frac{1}{"));
+    assert!(is_complete("(a +\n b)"));
+    assert!(is_complete("a)"));
+
+    // Only a trailing backslash which is not part of an operator continues the line.
+    assert!(!is_complete("a + \\"));
+    assert!(is_complete("a + \\\n b"));
+    assert!(is_complete(r"a /\"));
+
+    // Comments and commands are not counted.
+    assert!(is_complete("a # (b"));
+    assert!(is_complete("a # b \\"));
+    assert!(is_complete(":load (x"));
+}
+
+#[test]
+fn completions() {
+    let mut session = Session::new(Config::default());
+    session.set_algebra(parse_metric("4").unwrap()).unwrap();
+    let bases = bases(&session);
+    let variables = vec!["alpha".to_string()];
+    let complete = |line: &str| complete(line, line.len(), &variables, &bases);
+
+    assert_eq!(complete("e1"), (0, strings(&["e1", "e12", "e123", "e13"])));
+    assert_eq!(complete("a + al"), (4, strings(&["alpha"])));
+    assert_eq!(complete("as"), (0, strings(&["assert"])));
+    assert_eq!(complete(":re"), (1, strings(&["restore"])));
+    assert_eq!(complete(":load e"), (6, Vec::new()));
+
+    // All commands are completed and parsed alike.
+    let (_, commands) = complete(":");
+    assert_eq!(commands.len(), COMMANDS.len());
+    for command in commands {
+        let parsed = parse_command(&command);
+        assert!(
+            parsed.is_ok() || parsed.err().unwrap().starts_with("Missing file"),
+            "{command}"
+        );
+    }
+}
+
+fn strings(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn results() {
+    let mut session = Session::new(Config::default());
+    session.set_algebra(parse_metric("2").unwrap()).unwrap();
+    let mut execute = |text: &str| session.execute(&Input::new(text), false);
+    assert!(!execute("1 + e0").failed());
+    assert!(!execute("ans e0").failed());
+    assert!(!execute("a = $1").failed());
+    assert!(!execute(":history").failed());
+
+    assert_eq!(session.history, ["1 + e0", "ans e0"]);
+    assert_eq!(session.variables["$2"].to_string(), "1 + e0");
+    assert_eq!(session.variables["ans"], session.variables["$2"]);
+
+    // Results are kept after the metric changes, but only used in their own algebra.
+    let mut execute = |text: &str| session.execute(&Input::new(text), false);
+    assert!(!execute(":metric 3").failed());
+    assert!(!execute(":embed").failed());
+    assert!(execute("ans").undefined);
+    assert!(!execute("a").failed());
+    assert!(!execute(":history").failed());
+    assert!(!execute(":metric 2").failed());
+    assert!(!execute("$1 + $2").failed());
+    assert!(execute("$3").undefined);
+    assert_eq!(session.history.len(), 4);
+}
+
+#[test]
+fn aligned_assignments() {
+    let mut session = Session::new(Config::default());
+    session.set_algebra(parse_metric("2").unwrap()).unwrap();
+    assert!(!session.execute(&Input::new("e0 + e1"), false).failed());
+    let value = || session.variables["$1"].clone();
+
+    // Only interactive sessions echo assignments, unless they are listed in `align` blocks.
+    assert_eq!(session.echo_assignment("a", value(), None, false), None);
+    assert_eq!(
+        session.echo_assignment("a", value(), None, true).as_deref(),
+        Some("  a = e0 + e1\n")
+    );
+    session.format = Format::LatexAlign;
+    assert_eq!(
+        session
+            .echo_assignment("a", value(), Some("a".to_string()), false)
+            .as_deref(),
+        Some("\\begin{align}\n  a &= \\mathbf{e}_{0} + \\mathbf{e}_{1}\n\\end{align}\n")
+    );
+}
+
+#[test]
+fn saved_sessions() {
+    let config = || Config {
+        input: Some(Syntax::Latex),
+        ..Default::default()
+    };
+    let directory = env::temp_dir().join(format!("galc-session-{}", process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("session.ga");
+    let save = format!(":save {}", path.display());
+    let restore = format!(":restore {}", path.display());
+
+    // LaTeX inputs are saved as plain statements, which are restored as such.
+    let mut session = Session::new(config());
+    let mut execute = |text: &str| session.execute(&Input::new(text), false);
+    assert!(!execute(":metric pga 3").failed());
+    assert!(!execute(":basis e13").failed());
+    assert!(!execute(r"a = \frac{1}{2} \mathbf{e}_{31}").failed());
+    assert!(!execute(r"b = \left(1 + c\right)^{n}").failed());
+    // Restoring `d` first would substitute it into `r`.
+    assert!(!execute(r"r = d \mathbf{e}_{1}").failed());
+    assert!(!execute("d = 2").failed());
+    assert!(!execute(&save).failed());
+    let saved = fs::read_to_string(&path).unwrap();
+
+    let mut restored = Session::new(config());
+    let outcome = restored.execute(&Input::new(&restore), false);
+    fs::remove_dir_all(&directory).unwrap();
+
+    assert!(!outcome.failed(), "{saved}");
+    assert!(saved.contains("a = -1/2 e13\n"), "{saved}");
+    assert_eq!(restored.syntax, Syntax::Latex);
+    assert_eq!(restored.algebra, session.algebra);
+    assert_eq!(restored.variables, session.variables);
+}
@@ -1,9 +1,32 @@
-use crate::algebra::{
-    basis::Basis,
-    metric::{Metric, Square},
-    sign::Sign,
+use crate::{
+    algebra::{
+        basis::Basis,
+        latex::Latex,
+        metric::{Metric, Square},
+        notation::Notation,
+        polynom::Polynomial,
+        preset,
+        sign::Sign,
+    },
+    interpret::{eval, expr::Statement},
+    parse::{self, Syntax},
 };
 
+fn exec(input: &str, metric: &Metric) -> eval::Value {
+    let statement = parse::parse(input).expect("Syntax error");
+    match eval::exec(statement, metric, &mut Default::default()) {
+        Ok(value) => value,
+        Err(_) => panic!("Undefined"),
+    }
+}
+
+fn evaluate(input: &str, metric: &Metric) -> Polynomial {
+    match exec(input, metric) {
+        eval::Value::Polynomial(result) => result,
+        _ => panic!("Not a polynomial"),
+    }
+}
+
 #[test]
 fn geometric_hyperbolic() {
     let metric = Metric(vec![Square::Pos, Square::Pos]);
@@ -31,3 +54,384 @@ fn geometric_degenerate() {
     let b = Basis(vec![true, false]);
     assert!(a.geometric_product(&b, &metric).is_none());
 }
+
+#[test]
+fn collected_by_basis() {
+    let metric = Metric(vec![Square::Pos; 3]);
+    let p = evaluate("a e1 + b e2 + c e1", &metric);
+    assert_eq!(
+        p.collected(&Notation::default()).to_string(),
+        "(a + c) e1 + b e2"
+    );
+}
+
+#[test]
+fn collected_ordering_and_signs() {
+    let metric = Metric(vec![Square::Pos; 3]);
+    let p = evaluate("e12 - e2 + 2 - a e1 e0", &metric);
+    assert_eq!(
+        p.collected(&Notation::default()).to_string(),
+        "2 - e2 + a e01 + e12"
+    );
+    let p = evaluate("-e1", &metric);
+    assert_eq!(p.collected(&Notation::default()).to_string(), "-e1");
+}
+
+#[test]
+fn canonical_order() {
+    let metric = Metric(vec![Square::Pos; 3]);
+    let p = evaluate("b e1 + a + e0 e2 + 2 a e1", &metric);
+    let q = evaluate("e02 + 2 a e1 + a + b e1", &metric);
+    assert_eq!(p.to_string(), q.to_string());
+    assert_eq!(p.to_string(), "a + 2 a e1 + b e1 + e02");
+}
+
+#[test]
+fn algebraic_equality() {
+    let metric = Metric(vec![Square::Pos; 3]);
+    assert_eq!(evaluate("a + b", &metric), evaluate("b + a", &metric));
+    assert_eq!(evaluate("e0 e1", &metric), evaluate("-e1 e0", &metric));
+    assert_ne!(evaluate("e0 e1", &metric), evaluate("e1 e0", &metric));
+    assert_eq!(evaluate("a - a", &metric), evaluate("0", &metric));
+}
+
+#[test]
+fn comparison() {
+    let metric = Metric(vec![Square::Pos; 3]);
+    assert!(matches!(
+        exec("~(a e0 e1) == ~e1 ~e0 a", &metric),
+        eval::Value::Boolean(true)
+    ));
+    assert!(matches!(
+        exec("e0 e1 != e1 e0", &metric),
+        eval::Value::Boolean(true)
+    ));
+    assert!(matches!(
+        exec("assert a e0 == e0", &metric),
+        eval::Value::Assertion(false)
+    ));
+}
+
+#[test]
+fn cancellation_vanishes() {
+    let metric = Metric(vec![Square::Pos; 3]);
+    assert_eq!(evaluate("a e1 - a e1", &metric).to_string(), "0");
+    assert_eq!(evaluate("a e1 + b - a e1", &metric).to_string(), "b");
+    assert_eq!(
+        evaluate("(e0 + e1)(e0 - e1)", &metric).to_string(),
+        "-2 e01"
+    );
+    assert!(evaluate("0", &metric).is_zero());
+}
+
+#[test]
+fn exterior_square_vanishes() {
+    let metric = Metric(vec![Square::Pos; 3]);
+    assert!(evaluate(r"e1 /\ e1", &metric).is_zero());
+    assert_eq!(evaluate(r"e1 /\ e1 + e2", &metric).to_string(), "e2");
+}
+
+#[test]
+fn degenerate_product_vanishes() {
+    let metric = Metric(vec![Square::Pos, Square::Zero]);
+    assert!(evaluate("e1 e1", &metric).is_zero());
+    assert!(evaluate("e01 e1", &metric).is_zero());
+    assert_eq!(evaluate("(e0 + e1) e1", &metric).to_string(), "i");
+}
+
+#[test]
+fn syntax_error_location() {
+    let errors = parse::parse("(a + b").err().unwrap();
+    assert_eq!(errors[0].span, 6..7);
+    assert!(errors[0].message.contains("`)`"));
+
+    let errors = parse::parse("a + b)").err().unwrap();
+    assert_eq!(errors[0].span, 5..6);
+    assert!(errors[0].hint.is_some());
+}
+
+#[test]
+fn undefined_causes() {
+    let metric = Metric(vec![Square::Pos, Square::Zero]);
+    let undefined = |input| match eval::exec(
+        parse::parse(input).unwrap(),
+        &metric,
+        &mut Default::default(),
+    ) {
+        Err(causes) => causes,
+        Ok(_) => panic!("Defined"),
+    };
+
+    let causes = undefined("a / e1 + 1/0");
+    assert!(matches!(causes[0].0, eval::Undefined::NonInvertible));
+    assert_eq!(causes[0].1, 0..6);
+    assert!(matches!(causes[1].0, eval::Undefined::InvalidNumber(_)));
+    assert_eq!(causes[1].1, 9..12);
+
+    let causes = undefined("e02");
+    assert!(matches!(
+        causes[0].0,
+        eval::Undefined::BasisOutOfRange { dimension: 2, .. }
+    ));
+    assert!(matches!(undefined("_|_")[0].0, eval::Undefined::Bottom));
+}
+
+#[test]
+fn powers() {
+    let metric = Metric(vec![Square::Pos; 3]);
+    assert_eq!(
+        evaluate("(1 + e1)^2", &metric),
+        evaluate("2 + 2 e1", &metric)
+    );
+    assert_eq!(evaluate("a^(2 + 1)", &metric), evaluate("a a a", &metric));
+    assert_eq!(evaluate("(2 e1)^-2", &metric), evaluate("1/4", &metric));
+    assert_eq!(evaluate("(a + 1/2)^n", &metric).to_string(), "(1/2 + a)^n");
+    assert_eq!(evaluate("a^n", &metric), evaluate("a^n", &metric));
+    assert_eq!(evaluate("a^n a^n", &metric).to_string(), "(a^n)^2");
+    assert_eq!(evaluate("(a^n)^2", &metric), evaluate("a^n a^n", &metric));
+    assert_eq!(
+        evaluate("(-a)^(n + 1)", &metric).to_string(),
+        "(-a)^(1 + n)"
+    );
+    assert_eq!(evaluate("2^n e1", &metric).to_string(), "2^n e1");
+
+    // Symbolic powers are the same in every algebra.
+    assert_eq!(
+        evaluate("(1 + a)^n e1", &metric).embed(4),
+        Some(evaluate("(1 + a)^n e1", &Metric(vec![Square::Pos; 4])))
+    );
+}
+
+#[test]
+fn invalid_exponents() {
+    let metric = Metric(vec![Square::Pos; 3]);
+    let undefined = |input| match eval::exec(
+        parse::parse(input).unwrap(),
+        &metric,
+        &mut Default::default(),
+    ) {
+        Err(causes) => causes,
+        Ok(_) => panic!("Defined"),
+    };
+    let causes = undefined("a^99999999999999999999");
+    assert!(matches!(
+        causes[0].0,
+        eval::Undefined::ExponentOutOfRange(_)
+    ));
+    assert_eq!(causes[0].1, 2..22);
+    assert!(matches!(
+        undefined("a^(1/2)")[0].0,
+        eval::Undefined::NonIntegralExponent(_)
+    ));
+    assert!(matches!(
+        undefined("a^(n e1)")[0].0,
+        eval::Undefined::NonScalarExponent
+    ));
+
+    // Symbolic powers of multivectors would commute like scalars.
+    let causes = undefined("e1 (e12^n)");
+    assert!(matches!(causes[0].0, eval::Undefined::NonScalarBase));
+    assert_eq!(causes[0].1, 4..7);
+    assert!(matches!(
+        undefined("(e1 + e2 + a)^65536")[0].0,
+        eval::Undefined::TooManyTerms
+    ));
+
+    // Neither multiplicities nor coefficients of powers grow without bound.
+    assert!(matches!(
+        undefined("a^65536^65536^65536^65536")[0].0,
+        eval::Undefined::ExponentOutOfRange(_)
+    ));
+    let causes = undefined("2^65536^65536");
+    assert!(matches!(
+        causes[0].0,
+        eval::Undefined::ExponentOutOfRange(_)
+    ));
+    assert_eq!(causes[0].1, 8..13);
+}
+
+#[test]
+fn assignments() {
+    let metric = Metric(vec![Square::Pos; 3]);
+    let mut variables = eval::Variables::new();
+    let mut exec = |input| match eval::exec(parse::parse(input).unwrap(), &metric, &mut variables) {
+        Ok(eval::Value::Polynomial(result)) => result.to_string(),
+        Ok(eval::Value::Assignment(name, value)) => format!("{name} = {value}"),
+        _ => panic!("Neither a polynomial nor an assignment"),
+    };
+    assert_eq!(exec("R = e12"), "R = e12");
+    assert_eq!(exec("R a"), "a e12");
+    assert_eq!(exec("R = R R"), "R = -1");
+    assert_eq!(exec("R + b"), "-1 + b");
+}
+
+#[test]
+fn missing_results() {
+    let metric = Metric(vec![Square::Pos; 2]);
+    for input in ["ans", "$1"] {
+        let statement = parse::parse(input).unwrap();
+        assert!(matches!(
+            eval::exec(statement, &metric, &mut Default::default())
+                .err()
+                .unwrap()[0]
+                .0,
+            eval::Undefined::MissingResult(_)
+        ));
+    }
+}
+
+#[test]
+fn all_bases() {
+    let bases: Vec<String> = Basis::all(2).map(|basis| basis.to_string()).collect();
+    assert_eq!(bases, ["", "e0", "e1", "i"]);
+}
+
+#[test]
+fn embedding() {
+    let p = evaluate("a e1 + 2", &Metric::signature(3, 0, 0));
+    assert_eq!(p.clone().embed(2).unwrap().dimension(), Some(2));
+    assert_eq!(p.clone().embed(4).unwrap().to_string(), "2 + a e1");
+    assert!(p.clone().embed(1).is_none());
+
+    let mut variables = eval::Variables::new();
+    variables.insert("p".to_string(), p.clone());
+    variables.insert("ans".to_string(), p);
+    let result = eval::exec(parse::parse("p").unwrap(), &Metric::pga(3), &mut variables);
+    assert!(matches!(
+        &result.err().unwrap()[0].0,
+        eval::Undefined::ForeignVariable { dimension: 3, .. }
+    ));
+    let result = eval::exec(
+        parse::parse("ans").unwrap(),
+        &Metric::pga(3),
+        &mut variables,
+    );
+    assert!(matches!(
+        &result.err().unwrap()[0].0,
+        eval::Undefined::ForeignResult { dimension: 3, .. }
+    ));
+}
+
+#[test]
+fn presets() {
+    let metric = preset::preset("sta").unwrap().metric();
+    assert_eq!(evaluate("e0 e0", &metric).to_string(), "1");
+    assert_eq!(evaluate("e1 e1", &metric).to_string(), "-1");
+
+    let quaternions = preset::preset("quaternion").unwrap().metric();
+    assert_eq!(evaluate("e0 e1 e01", &quaternions).to_string(), "-1");
+
+    let dual_quaternion = preset::preset("dual-quaternion").unwrap();
+    let (metric, notation) = (dual_quaternion.metric(), dual_quaternion.notation());
+    let evaluate = |input: &str| {
+        let statements =
+            parse::parse_statements(input, &notation, Syntax::Plain).expect("Syntax error");
+        match eval::exec(statements[0].clone(), &metric, &mut Default::default()) {
+            Ok(eval::Value::Polynomial(result)) => result.collected(&notation).to_string(),
+            _ => panic!("Not a polynomial"),
+        }
+    };
+    assert_eq!(evaluate("i j k"), "-1");
+    assert_eq!(evaluate("ε^2"), "0");
+    assert_eq!(evaluate("ε i + ε j + ε k - εi"), "εj + εk");
+    assert_eq!(evaluate("i e1"), "-e123");
+}
+
+#[test]
+fn oriented_bases() {
+    let mut notation = Notation::pga(3);
+    let metric = Metric::pga(3);
+    let evaluate = |input: &str, notation: &Notation| {
+        let statements =
+            parse::parse_statements(input, notation, Syntax::Plain).expect("Syntax error");
+        match eval::exec(statements[0].clone(), &metric, &mut Default::default()) {
+            Ok(eval::Value::Polynomial(result)) => result.collected(notation).to_string(),
+            _ => panic!("Not a polynomial"),
+        }
+    };
+    assert_eq!(evaluate("e1 e3 + e0 e2 e3", &notation), "-e31 - e032");
+    assert_eq!(evaluate("e31 - e01", &notation), "e31 - e01");
+
+    notation.orient(notation.blade("e13").unwrap(), "e13".to_string());
+    assert_eq!(evaluate("e1 e3 + e31", &notation), "0");
+    assert_eq!(evaluate("e1 e3", &notation), "e13");
+    assert_eq!(evaluate("(1 + a)^n e3 e1", &notation), "-(1 + a)^n e13");
+    assert!(notation.blade("e11").is_none());
+}
+
+#[test]
+fn latex() {
+    let notation = Notation::pga(3);
+    let latex = Latex::new(&notation, None);
+    let metric = Metric::pga(3);
+    let statement = parse::parse_statements(
+        "1/2 a^2 e1 e2 - e1 e3 + b e1 + c e1",
+        &notation,
+        Syntax::Plain,
+    )
+    .expect("Syntax error")
+    .remove(0);
+    let Statement::Expr(expr) = &statement else {
+        panic!("Not an expression")
+    };
+    assert_eq!(
+        expr.0.latex(&latex),
+        r"\frac{1}{2} a^{2} \mathbf{e}_{1} \mathbf{e}_{2} - \mathbf{e}_{1} \mathbf{e}_{3} + b \mathbf{e}_{1} + c \mathbf{e}_{1}"
+    );
+    match eval::exec(statement, &metric, &mut Default::default()) {
+        Ok(eval::Value::Polynomial(result)) => assert_eq!(
+            result.collected(&latex).to_string(),
+            r"\left(b + c\right)\, \mathbf{e}_{1} + \frac{1}{2} a^{2}\, \mathbf{e}_{12} + \mathbf{e}_{31}"
+        ),
+        _ => panic!("Not a polynomial"),
+    }
+
+    // Inputs and results with powers of powers, which LaTeX only accepts in braces.
+    let both = |input: &str| {
+        let statement = parse::parse_statements(input, &notation, Syntax::Plain)
+            .expect("Syntax error")
+            .remove(0);
+        let Statement::Expr(expr) = &statement else {
+            panic!("Not an expression")
+        };
+        let input = expr.0.latex(&latex);
+        match eval::exec(statement, &metric, &mut Default::default()) {
+            Ok(eval::Value::Polynomial(result)) => (input, result.collected(&latex).to_string()),
+            _ => panic!("Not a polynomial"),
+        }
+    };
+    assert_eq!(both("a^2^3"), (r"{a^{2}}^{3}".into(), r"a^{6}".into()));
+    assert_eq!(
+        both("*(b^-1)"),
+        (r"{b^{-1}}^{*}".into(), r"b^{-1}\, I".into())
+    );
+    assert_eq!(
+        both("(1/2 + a)^n (1/2 + a)^n (-b)^m e3 e1"),
+        (
+            r"\left(\frac{1}{2} + a\right)^{n} \left(\frac{1}{2} + a\right)^{n} \left(-b\right)^{m} \mathbf{e}_{3} \mathbf{e}_{1}".into(),
+            r"{\left(\frac{1}{2} + a\right)^{n}}^{2} \left(-b\right)^{m}\, \mathbf{e}_{31}".into()
+        )
+    );
+
+    // Grade projections and postfix duals are only written in LaTeX.
+    let evaluate = |input: &str| {
+        let statement = parse::parse_statements(input, &notation, Syntax::Latex)
+            .expect("Syntax error")
+            .remove(0);
+        match eval::exec(statement, &metric, &mut Default::default()) {
+            Ok(eval::Value::Polynomial(result)) => result.collected(&notation).to_string(),
+            _ => panic!("Not a polynomial"),
+        }
+    };
+    assert_eq!(evaluate(r"\langle 1 + e_1 + e_{12} \rangle_2"), "e12");
+    assert_eq!(evaluate(r"\langle 1 + e_1 + e_{12} \rangle"), "1");
+    assert_eq!(evaluate(r"a^{*}"), "a i");
+
+    let sta = preset::preset("sta").unwrap().notation();
+    let latex = Latex::new(&sta, None);
+    assert_eq!(latex.vectors(&[0, 1]), r"\gamma_{01}");
+    assert_eq!(
+        Latex::new(&sta, Some(r"\sigma")).vectors(&[2]),
+        r"\sigma_{2}"
+    );
+}